@@ -1,36 +1,643 @@
-use std::env;
+use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::Utc;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use dotenvy::dotenv;
-use greentic_demo::loader::load_packs;
-use greentic_demo::runner_shim::{self, RunnerConfig};
+use futures::StreamExt;
+use greentic_demo::chat::{self, ChatSession};
+use greentic_demo::config::{AppConfig, CliArgs, Mode, NatsAuth, PackConfig};
+use greentic_demo::duration::parse_duration;
+use greentic_demo::health::HealthMonitor;
+use greentic_demo::loader::{LoadReport, discover_tenants, record_served};
+use greentic_demo::logging;
+use greentic_demo::nats_bridge::{self, NatsBridge};
+use greentic_demo::pack_signing::{self, PackVerifier};
+use greentic_demo::runner_bridge::RunnerBridge;
+use greentic_demo::runner_shim::{self, RunnerConfig, RunnerEnv};
+use greentic_demo::secret_bundle::{self, BundleKey, SealedBundle};
+use greentic_demo::secret_rotation::SecretWatcher;
+use greentic_demo::types::{Activity, ActivityType, ChannelAccount, ConversationAccount};
+use greentic_demo::validate::ValidationReport;
+#[cfg(feature = "use-runner-api")]
+use greentic_demo::{bindings, loader::TenantPack};
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing_subscriber::EnvFilter;
+use uuid::Uuid;
 
 #[cfg(not(any(feature = "runner-shim", feature = "use-runner-api")))]
 compile_error!("either runner-shim or use-runner-api must be enabled");
 
+#[derive(Debug, Parser)]
+#[command(author, version, about = "greentic demo runner", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Serves tenants with one of the deployment models.
+    #[command(subcommand)]
+    Serve(ServeCommand),
+    /// Lists the tenants that would be served and why others are skipped.
+    Tenants(TenantsArgs),
+    /// Publishes a message activity to a tenant's ingress subject and prints the replies.
+    Send(SendArgs),
+    /// Chats with one tenant's messaging flow from stdin, without NATS.
+    Chat(ChatArgs),
+    /// Pack maintenance utilities.
+    #[command(subcommand)]
+    Pack(PackCommand),
+    /// Secrets bundle utilities.
+    #[command(subcommand)]
+    Secrets(SecretsCommand),
+    /// Bridge configuration utilities.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Loads every tenant like the server would and reports problems without serving.
+    Validate(ValidateArgs),
+}
+
+#[derive(Debug, Subcommand)]
+enum ServeCommand {
+    /// Runs greentic-runner-host (or the runner-shim fallback) from env vars; the default
+    /// when no command is given.
+    Runner,
+    /// Bridges per-tenant NATS subjects to the tenants' messaging flows.
+    Nats {
+        #[command(flatten)]
+        args: Box<CliArgs>,
+    },
+}
+
+#[derive(Debug, clap::Args)]
+struct TenantsArgs {
+    /// Print the tenants as JSON.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+    #[command(flatten)]
+    args: CliArgs,
+}
+
+#[derive(Debug, clap::Args)]
+struct SendArgs {
+    /// Tenant whose ingress subject receives the activity.
+    #[arg(long)]
+    tenant: String,
+    /// Message text.
+    text: String,
+    /// Conversation id; a new one is generated when omitted.
+    #[arg(long)]
+    conversation: Option<String>,
+    /// How long to collect replies from the egress subject (e.g. 5s, 1m).
+    #[arg(long, default_value = "5s", value_parser = parse_duration)]
+    wait: Duration,
+    /// Print replies as JSON activities.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+    #[command(flatten)]
+    args: CliArgs,
+}
+
+#[derive(Debug, clap::Args)]
+struct ChatArgs {
+    /// Tenant to chat with.
+    #[arg(long)]
+    tenant: String,
+    /// Conversation id kept for the whole session; a new one is generated when omitted.
+    #[arg(long)]
+    conversation: Option<String>,
+    /// Canned tool, node and secret responses (see flow mocks) so nothing leaves the machine.
+    #[arg(long)]
+    mocks: Option<PathBuf>,
+    /// Print outgoing activities as JSON instead of readable summaries.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+    #[command(flatten)]
+    args: CliArgs,
+}
+
+#[derive(Debug, clap::Args)]
+struct ValidateArgs {
+    /// Print the report as JSON.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+    #[command(flatten)]
+    args: CliArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum PackCommand {
+    /// Writes a detached Ed25519 signature (`<pack>.sig`) for a pack.
+    Sign {
+        /// Pack to sign: index.ygtc, a .gtpack directory or a .gtpack archive.
+        pack: PathBuf,
+        /// File holding the base64 32-byte Ed25519 seed (e.g. `openssl rand -base64 32`).
+        #[arg(long, env = "PACK_SIGNING_KEY")]
+        key: PathBuf,
+        /// Signature output path; defaults to `<pack>.sig`.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Prints the effective bridge configuration and where each value came from.
+    Print {
+        /// Masks credentials (NATS JWT/seed, URL passwords, telemetry payload).
+        #[arg(long, default_value_t = false)]
+        redacted: bool,
+        /// Print the entries as JSON.
+        #[arg(long, default_value_t = false)]
+        json: bool,
+        #[command(flatten)]
+        args: CliArgs,
+    },
+    /// Validates the effective configuration and lists every error and warning at once.
+    Check {
+        /// Print the report as JSON.
+        #[arg(long, default_value_t = false)]
+        json: bool,
+        #[command(flatten)]
+        args: CliArgs,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SecretsCommand {
+    /// Encrypts a plaintext YAML/JSON map of secret names to values into a bundle.
+    Seal {
+        /// Plaintext secrets file; keep it out of version control.
+        input: PathBuf,
+        /// Base64 32-byte AES-256-GCM key (e.g. `openssl rand -base64 32`).
+        #[arg(long, env = "GREENTIC_SECRETS_KEY", hide_env_values = true)]
+        key: String,
+        /// Bundle output path, safe to commit.
+        #[arg(long, default_value = "secrets.bundle.json")]
+        out: PathBuf,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
-    init_tracing();
-    let bindings = discover_binding_files()?;
-    let cfg = RunnerConfig::from_env(bindings)?;
+    let matches = Cli::command().get_matches();
+    let command = Cli::from_arg_matches(&matches)?.command;
+    match &command {
+        // `serve nats` installs its own subscriber (dev log file or JSON + telemetry).
+        Some(Command::Serve(ServeCommand::Nats { .. })) => {}
+        // Keep stdout for the conversation.
+        Some(Command::Chat(_)) => {
+            let filter =
+                EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+            let _ = tracing_subscriber::fmt()
+                .with_env_filter(filter)
+                .with_writer(std::io::stderr)
+                .try_init();
+        }
+        _ => init_tracing(),
+    }
+    match command {
+        Some(Command::Pack(PackCommand::Sign { pack, key, out })) => sign_pack(&pack, &key, out),
+        Some(Command::Secrets(SecretsCommand::Seal { input, key, out })) => {
+            seal_secrets(&input, &key, &out)
+        }
+        Some(Command::Config(ConfigCommand::Print { redacted, json, .. })) => print_config(
+            subcommand_matches(&matches, &["config", "print"])?,
+            redacted,
+            json,
+        ),
+        Some(Command::Config(ConfigCommand::Check { json, .. })) => {
+            check_config(subcommand_matches(&matches, &["config", "check"])?, json)
+        }
+        Some(Command::Validate(args)) => {
+            validate(subcommand_matches(&matches, &["validate"])?, args).await
+        }
+        Some(Command::Tenants(args)) => {
+            list_tenants(subcommand_matches(&matches, &["tenants"])?, args).await
+        }
+        Some(Command::Send(args)) => send(subcommand_matches(&matches, &["send"])?, args).await,
+        Some(Command::Chat(args)) => chat(subcommand_matches(&matches, &["chat"])?, args).await,
+        Some(Command::Serve(ServeCommand::Nats { .. })) => {
+            serve_nats(subcommand_matches(&matches, &["serve", "nats"])?).await
+        }
+        Some(Command::Serve(ServeCommand::Runner)) | None => serve_runner().await,
+    }
+}
+
+async fn serve_runner() -> Result<()> {
+    let env = RunnerEnv::from_env()?;
+    let tenants = env.discover(env.packs_strict).await?;
+    #[cfg(feature = "use-runner-api")]
+    let cfg = {
+        check_runner_bindings(&tenants)?;
+        RunnerConfig::from_env(
+            tenants
+                .iter()
+                .map(|pack| pack.bindings_path.clone())
+                .collect(),
+        )?
+    };
+    #[cfg(not(feature = "use-runner-api"))]
+    let cfg = RunnerConfig::new(env, tenants);
     runner_shim::run(cfg).await
 }
 
+/// greentic-runner-host reads the rendered bindings from disk, so `${secret:NAME}` and
+/// `$${` escapes would reach it as literal text.
+#[cfg(feature = "use-runner-api")]
+fn check_runner_bindings(tenants: &[TenantPack]) -> Result<()> {
+    for pack in tenants {
+        if bindings::needs_render(&pack.bindings_path)? {
+            bail!(
+                "tenant {}: {} uses ${{secret:NAME}} or $${{, which greentic-runner-host cannot \
+                 resolve; serve it with the runner-shim build",
+                pack.tenant,
+                pack.bindings_source.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn serve_nats(matches: &ArgMatches) -> Result<()> {
+    let config = AppConfig::from_matches(matches)?;
+    let _logging = logging::init_logging(&config)?;
+    config.log_startup_warnings();
+    config.validate()?;
+
+    let verifier = PackVerifier::from_key(config.pack_public_key.as_deref())?;
+    let packs = discover_tenants(
+        &config.packs_dir,
+        config.pack_index.as_deref(),
+        &config.pack_cache_dir,
+        &verifier,
+    )
+    .await?
+    .into_tenants(config.strict_packs)?;
+    record_served(&config.pack_cache_dir, &packs)?;
+
+    let mut runner = RunnerBridge::new(config.mode.clone(), config.secret_policy.clone());
+    if let Some(path) = &config.flow_audit_log {
+        runner = runner.with_flow_audit(path)?;
+    }
+    let mut tenants = Vec::new();
+    for pack in &packs {
+        match runner.register_pack(pack).await {
+            Ok(()) => tenants.push(pack.tenant.clone()),
+            Err(err) if config.strict_packs => return Err(err),
+            Err(err) => {
+                tracing::error!(tenant = %pack.tenant, error = %format!("{err:#}"), "failed to register tenant; skipping");
+            }
+        }
+    }
+
+    let health = HealthMonitor::new(Duration::from_secs(60));
+    let _reporter = health.spawn_reporter();
+    let bridge = NatsBridge::connect(&config, runner, tenants, health).await?;
+    let _watcher = match config.nats.auth {
+        NatsAuth::Jwt { .. } => {
+            let mut watcher = SecretWatcher::from_env()?;
+            bridge.watch_credentials(&mut watcher);
+            Some(watcher.spawn())
+        }
+        NatsAuth::None => None,
+    };
+    bridge.run().await
+}
+
+async fn list_tenants(matches: &ArgMatches, args: TenantsArgs) -> Result<()> {
+    let config = PackConfig::from_matches(matches)?;
+    let load = discover_packs(&config).await?;
+    if args.json {
+        let tenants: Vec<_> = load
+            .tenants
+            .iter()
+            .map(|pack| {
+                json!({
+                    "tenant": pack.tenant,
+                    "digest": pack.digest,
+                    "pack": pack.index_path,
+                    "bindings": pack.bindings_source,
+                    "overlays": pack.overlays,
+                })
+            })
+            .collect();
+        let report = json!({ "tenants": tenants, "problems": load.problems });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return fail_if_strict(&config, &load);
+    }
+
+    let width = load
+        .tenants
+        .iter()
+        .map(|pack| pack.tenant.len())
+        .max()
+        .unwrap_or(0);
+    for pack in &load.tenants {
+        println!(
+            "{:<width$}  {}  {}",
+            pack.tenant,
+            pack.digest,
+            pack.index_path.display()
+        );
+        for overlay in &pack.overlays {
+            println!("{:<width$}    + {}", "", overlay.display());
+        }
+    }
+    for problem in &load.problems {
+        println!("skipped: {problem}");
+    }
+    println!(
+        "{} tenant(s), {} problem(s)",
+        load.tenants.len(),
+        load.problems.len()
+    );
+    fail_if_strict(&config, &load)
+}
+
+/// `--strict-packs` turns any skipped pack into a failure, as it does for `serve`.
+fn fail_if_strict(config: &PackConfig, load: &LoadReport) -> Result<()> {
+    if config.strict_packs && !load.is_clean() {
+        bail!(
+            "strict pack loading found {} problem(s)",
+            load.problems.len()
+        );
+    }
+    Ok(())
+}
+
+async fn send(matches: &ArgMatches, args: SendArgs) -> Result<()> {
+    let config = AppConfig::from_matches(matches)?;
+    let client = nats_bridge::connect(&config.nats).await?;
+    let ingress = config.subjects.ingress_subject(&args.tenant);
+    let egress = config.subjects.egress_subject(&args.tenant);
+    let mut replies = client
+        .subscribe(egress.clone())
+        .await
+        .with_context(|| format!("failed to subscribe to {egress}"))?;
+
+    let conversation = args
+        .conversation
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let activity = Activity {
+        activity_type: ActivityType::Message,
+        id: Some(Uuid::new_v4().to_string()),
+        timestamp: Some(Utc::now()),
+        channel_id: Some("greentic-demo".into()),
+        from: Some(ChannelAccount {
+            id: Some("greentic-demo-cli".into()),
+            name: None,
+        }),
+        conversation: Some(ConversationAccount {
+            id: Some(conversation.clone()),
+            name: None,
+        }),
+        text: Some(args.text),
+        channel_data: Some(json!({ "tenant": args.tenant })),
+        ..Activity::default()
+    };
+    client
+        .publish(ingress.clone(), serde_json::to_vec(&activity)?.into())
+        .await
+        .with_context(|| format!("failed to publish to {ingress}"))?;
+    client
+        .flush()
+        .await
+        .context("failed to flush NATS client")?;
+
+    let mut received = 0;
+    let deadline = tokio::time::sleep(args.wait);
+    tokio::pin!(deadline);
+    loop {
+        let message = tokio::select! {
+            _ = &mut deadline => break,
+            message = replies.next() => match message {
+                Some(message) => message,
+                None => break,
+            },
+        };
+        let Ok(reply) = serde_json::from_slice::<Activity>(&message.payload) else {
+            continue;
+        };
+        let same_conversation = reply
+            .conversation
+            .as_ref()
+            .and_then(|conversation| conversation.id.as_deref())
+            == Some(conversation.as_str());
+        if !same_conversation {
+            continue;
+        }
+        received += 1;
+        if args.json {
+            println!("{}", serde_json::to_string(&reply)?);
+        } else {
+            match &reply.text {
+                Some(text) => println!("{text}"),
+                None => println!(
+                    "[{}] {}",
+                    reply.activity_type,
+                    serde_json::to_string(&reply)?
+                ),
+            }
+        }
+    }
+    if received == 0 {
+        bail!(
+            "no reply on {egress} within {:?}; is `greentic-demo serve nats` running?",
+            args.wait
+        );
+    }
+    Ok(())
+}
+
 fn init_tracing() {
     let _ = tracing_subscriber::fmt::try_init();
 }
 
-fn discover_binding_files() -> Result<Vec<PathBuf>> {
-    let packs_dir = env::var("PACKS_DIR").unwrap_or_else(|_| "./packs".into());
-    let packs_path = Path::new(&packs_dir);
-    let tenants = load_packs(packs_path)
-        .with_context(|| format!("failed to load tenant packs from {packs_dir}"))?;
+fn sign_pack(pack: &Path, key: &Path, out: Option<PathBuf>) -> Result<()> {
+    let scratch = tempfile::Builder::new()
+        .prefix("greentic-demo-sign-")
+        .tempdir()
+        .context("failed to create a scratch directory")?;
+    let (signature, public_key, digest) = pack_signing::pack_content(pack, scratch.path())
+        .and_then(|content| pack_signing::sign(&content, key))
+        .with_context(|| format!("failed to sign {}", pack.display()))?;
 
-    if tenants.is_empty() {
-        bail!("no tenant bindings found in {packs_dir}; add at least one pack");
+    let out = out.unwrap_or_else(|| pack_signing::signature_path(pack));
+    fs::write(&out, format!("{signature}\n"))
+        .with_context(|| format!("failed to write {}", out.display()))?;
+    println!("digest: {digest}");
+    println!("signature: {}", out.display());
+    println!("PACK_PUBLIC_KEY={public_key}");
+    Ok(())
+}
+
+fn seal_secrets(input: &Path, key: &str, out: &Path) -> Result<()> {
+    let key = BundleKey::from_base64(key)?;
+    let secrets = secret_bundle::read_plaintext(input)?;
+    SealedBundle::seal(&secrets, &key)?.write(out)?;
+    println!("sealed {} secret(s) into {}", secrets.len(), out.display());
+    Ok(())
+}
+
+fn print_config(matches: &ArgMatches, redacted: bool, json: bool) -> Result<()> {
+    let config = AppConfig::from_matches(matches)?;
+    let entries = config.entries(redacted);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else {
+        if let Some(path) = &config.config_file {
+            println!("# config file: {}", path.display());
+        }
+        let width = entries
+            .iter()
+            .map(|entry| entry.key.len())
+            .max()
+            .unwrap_or(0);
+        for entry in &entries {
+            println!("{:<width$}  {}  ({})", entry.key, entry.value, entry.source);
+        }
+        for warning in &config.warnings {
+            println!("# warning: {warning}");
+        }
     }
+    Ok(())
+}
 
-    Ok(tenants.into_iter().map(|pack| pack.bindings_path).collect())
+fn check_config(matches: &ArgMatches, json: bool) -> Result<()> {
+    let report = AppConfig::for_check(matches)?.check();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{report}");
+    }
+    if !report.ok {
+        bail!("configuration has {} error(s)", report.errors().count());
+    }
+    Ok(())
+}
+
+async fn chat(matches: &ArgMatches, args: ChatArgs) -> Result<()> {
+    let config = PackConfig::from_matches(matches)?;
+    let tenants = discover_packs(&config)
+        .await?
+        .into_tenants(config.strict_packs)?;
+    let Some(pack) = tenants.iter().find(|pack| pack.tenant == args.tenant) else {
+        let known: Vec<&str> = tenants.iter().map(|pack| pack.tenant.as_str()).collect();
+        bail!(
+            "tenant {} not found; available: {}",
+            args.tenant,
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known.join(", ")
+            }
+        );
+    };
+
+    let runner = RunnerBridge::new(Mode::Dev, config.secret_policy);
+    match &args.mocks {
+        Some(mocks) => runner.register_pack_with_mocks(pack, mocks).await?,
+        None => runner.register_pack(pack).await?,
+    }
+    let conversation = args
+        .conversation
+        .unwrap_or_else(|| format!("chat-{}", Uuid::new_v4()));
+    let mut session = ChatSession::new(runner, &args.tenant, conversation);
+    eprintln!(
+        "chatting with {} (conversation {}); /new starts a new conversation, /quit or Ctrl-D exits",
+        session.tenant(),
+        session.conversation()
+    );
+
+    let interactive = std::io::stdin().is_terminal();
+    let mut stdout = tokio::io::stdout();
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        if interactive {
+            stdout.write_all(b"you> ").await?;
+            stdout.flush().await?;
+        }
+        let Some(line) = lines.next_line().await? else {
+            break;
+        };
+        let line = line.trim();
+        match line {
+            "" => continue,
+            "/quit" | "/exit" => break,
+            "/new" => {
+                session.restart(format!("chat-{}", Uuid::new_v4()));
+                eprintln!("new conversation {}", session.conversation());
+                continue;
+            }
+            _ => {}
+        }
+        match session.send(line).await {
+            Ok(replies) => {
+                for reply in &replies {
+                    if args.json {
+                        println!("{}", serde_json::to_string(reply)?);
+                    } else {
+                        for rendered in chat::render(reply) {
+                            println!("bot> {rendered}");
+                        }
+                    }
+                }
+                if replies.is_empty() && !args.json {
+                    println!("bot> (no reply)");
+                }
+            }
+            Err(err) => eprintln!("error: {err:#}"),
+        }
+    }
+    Ok(())
+}
+
+/// Matches of the subcommand at `path`, which carry the flattened bridge arguments.
+/// The matches of the subcommand at `path`, which [`CliArgs`]-based configs layer over.
+fn subcommand_matches<'a>(matches: &'a ArgMatches, path: &[&str]) -> Result<&'a ArgMatches> {
+    path.iter().try_fold(matches, |matches, name| {
+        matches
+            .subcommand_matches(name)
+            .ok_or_else(|| anyhow!("no arguments parsed for `{}`", path.join(" ")))
+    })
+}
+
+/// Discovers tenants from the layered pack settings, like `serve nats` does.
+async fn discover_packs(config: &PackConfig) -> Result<LoadReport> {
+    let verifier = PackVerifier::from_key(config.pack_public_key.as_deref())?;
+    discover_tenants(
+        &config.packs_dir,
+        config.pack_index.as_deref(),
+        &config.pack_cache_dir,
+        &verifier,
+    )
+    .await
+}
+
+async fn validate(matches: &ArgMatches, args: ValidateArgs) -> Result<()> {
+    let config = PackConfig::from_matches(matches)?;
+    let load = discover_packs(&config).await?;
+    let report = ValidationReport::run(&load, &config.secret_policy).await;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{report}");
+    }
+    if !report.ok {
+        bail!(
+            "validation failed: {} tenant(s) failed, {} load problem(s)",
+            report.failed_tenants(),
+            report.problems.len()
+        );
+    }
+    Ok(())
 }
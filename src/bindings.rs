@@ -0,0 +1,499 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use greentic_runner_host::config::{
+    BindingsFile, HostConfig, SecretsPolicy, WebhookBindingConfig, WebhookPolicy,
+};
+use serde_yaml_bw as serde_yaml;
+use serde_yaml_bw::{Mapping, Value};
+use sha2::{Digest, Sha256};
+
+use crate::secret_policy::SecretPolicy;
+use crate::secrets;
+
+/// Key naming the bindings file a tenant's bindings inherit from.
+pub const EXTENDS_KEY: &str = "extends";
+
+/// Directory under the pack cache that holds rendered bindings files.
+pub const RESOLVED_DIR: &str = "resolved";
+
+/// Prefix of the environment variables `${ENV_VAR}` placeholders may read.
+pub const ENV_PREFIX: &str = "BINDINGS_";
+
+/// Resolves a bindings file: merges `extends:` chains (the extending file wins) and
+/// interpolates `${BINDINGS_*}` environment variables and `${secret:NAME}` for secrets
+/// `policy` grants to `tenant`; `$${` escapes a literal `${`.
+pub fn resolve(path: &Path, tenant: &str, policy: &SecretPolicy) -> Result<Value> {
+    resolve_with(path, tenant, Secrets::Resolve(policy))
+}
+
+/// Like [`resolve`], taking `${secret:NAME}` only from `secrets` (a test's mocks).
+pub fn resolve_with_secrets(
+    path: &Path,
+    tenant: &str,
+    secrets: &BTreeMap<String, String>,
+) -> Result<Value> {
+    resolve_with(path, tenant, Secrets::Mocked(secrets))
+}
+
+/// What interpolation does with `${secret:NAME}`.
+#[derive(Debug, Clone, Copy)]
+enum Secrets<'a> {
+    /// Read granted secrets through the provider chain.
+    Resolve(&'a SecretPolicy),
+    /// Leave the placeholder (and `$${` escapes) for a later [`Secrets::Resolve`] pass.
+    Keep,
+    Mocked(&'a BTreeMap<String, String>),
+}
+
+fn resolve_with(path: &Path, tenant: &str, secrets: Secrets<'_>) -> Result<Value> {
+    let mut value = load_chain(path, &mut Vec::new())?;
+    interpolate(&mut value, tenant, secrets)
+        .with_context(|| format!("failed to interpolate {}", path.display()))?;
+    Ok(value)
+}
+
+/// Builds the runner's [`HostConfig`] from a bindings file, resolving `${secret:NAME}` in
+/// memory so secret values never reach the disk.
+pub fn host_config(path: &Path, tenant: &str, policy: &SecretPolicy) -> Result<HostConfig> {
+    host_config_from(path, resolve(path, tenant, policy)?)
+}
+
+/// Builds the [`HostConfig`] for the bindings file at `path` from its resolved `value`.
+pub fn host_config_from(path: &Path, value: Value) -> Result<HostConfig> {
+    let bindings: BindingsFile = serde_yaml::from_value(value)
+        .with_context(|| format!("failed to parse bindings file {}", path.display()))?;
+
+    // Mirrors `HostConfig::load_from_path`.
+    let secrets_policy = SecretsPolicy::from_allowed(
+        bindings
+            .flow_type_bindings
+            .values()
+            .flat_map(|binding| binding.secrets.iter().cloned()),
+    );
+    let http_enabled = bindings
+        .mcp
+        .http_enabled
+        .unwrap_or(bindings.flow_type_bindings.contains_key("messaging"));
+    let webhook_policy = bindings
+        .flow_type_bindings
+        .get("webhook")
+        .and_then(|binding| {
+            serde_yaml::from_value::<WebhookBindingConfig>(binding.config.clone())
+                .map_err(|err| {
+                    tracing::warn!(error = %err, "failed to parse webhook binding config");
+                })
+                .ok()
+        })
+        .map(WebhookPolicy::from)
+        .unwrap_or_default();
+    Ok(HostConfig {
+        tenant: bindings.tenant,
+        bindings_path: path.to_path_buf(),
+        flow_type_bindings: bindings.flow_type_bindings,
+        mcp: bindings.mcp,
+        rate_limits: bindings.rate_limits,
+        http_enabled,
+        secrets_policy,
+        webhook_policy,
+        timers: bindings.timers,
+    })
+}
+
+/// Whether `path` uses `extends:` or placeholders and must be rendered before the runner
+/// can read it.
+pub fn needs_render(path: &Path) -> Result<bool> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if contents.contains("${") {
+        return Ok(true);
+    }
+    let value: Value = serde_yaml::from_str(&contents)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(value.get(EXTENDS_KEY).is_some())
+}
+
+/// Returns the bindings file the runner should load for `source`, rendered into `out_dir`
+/// with secrets left as placeholders when it uses `extends:` or placeholders. Relative
+/// `mcp.store` paths stay anchored to the directory of `source`.
+pub fn render(source: &Path, tenant: &str, out_dir: &Path) -> Result<PathBuf> {
+    if !needs_render(source)? {
+        return Ok(source.to_path_buf());
+    }
+    let mut value = resolve_with(source, tenant, Secrets::Keep)?;
+    let absolute = fs::canonicalize(source)
+        .with_context(|| format!("failed to read bindings file {}", source.display()))?;
+    if let Some(dir) = absolute.parent() {
+        anchor_store_paths(&mut value, dir);
+    }
+    let rendered = serde_yaml::to_string(&value)
+        .with_context(|| format!("failed to serialize {}", source.display()))?;
+
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("failed to create {}", out_dir.display()))?;
+    let key = hex::encode(Sha256::digest(absolute.to_string_lossy().as_bytes()));
+    let target = out_dir.join(format!("{}.yaml", &key[..16]));
+    write_private(&target, rendered.as_bytes())?;
+    tracing::debug!(source = %source.display(), rendered = %target.display(), "bindings rendered");
+    Ok(target)
+}
+
+/// Makes the `mcp.store` paths the runner resolves against the bindings file's directory
+/// absolute, including the default `http-single` cache dir.
+fn anchor_store_paths(value: &mut Value, dir: &Path) {
+    let Value::Mapping(root) = value else {
+        return;
+    };
+    let Some(Value::Mapping(mcp)) = root.get_mut("mcp") else {
+        return;
+    };
+    let Some(Value::Mapping(store)) = mcp.get_mut("store") else {
+        return;
+    };
+    let kind = store
+        .get("kind")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    match kind.as_deref() {
+        Some("local-dir") => anchor(store, "path", dir),
+        Some("http-single") => {
+            if store.get("cache_dir").is_none() && std::env::var_os("GREENTIC_CACHE_DIR").is_none()
+            {
+                // The runner's default when neither is set.
+                store.insert(
+                    Value::String("cache_dir".into(), None),
+                    Value::String(".greentic/tool-cache".into(), None),
+                );
+            }
+            anchor(store, "cache_dir", dir);
+        }
+        _ => {}
+    }
+}
+
+fn anchor(store: &mut Mapping, key: &str, dir: &Path) {
+    if let Some(Value::String(path, _)) = store.get_mut(key)
+        && Path::new(path.as_str()).is_relative()
+    {
+        *path = dir.join(path.as_str()).to_string_lossy().into_owned();
+    }
+}
+
+fn load_chain(path: &Path, seen: &mut Vec<PathBuf>) -> Result<Value> {
+    let absolute = fs::canonicalize(path)
+        .with_context(|| format!("failed to read bindings file {}", path.display()))?;
+    if seen.contains(&absolute) {
+        bail!(
+            "bindings {} extend themselves ({})",
+            path.display(),
+            seen.iter()
+                .chain(std::iter::once(&absolute))
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        );
+    }
+    seen.push(absolute);
+
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read bindings file {}", path.display()))?;
+    let mut value: Value = serde_yaml::from_str(&contents)
+        .with_context(|| format!("failed to parse bindings file {}", path.display()))?;
+    let Value::Mapping(map) = &mut value else {
+        bail!("bindings file {} is not a mapping", path.display());
+    };
+    let Some(parent) = map.remove(EXTENDS_KEY) else {
+        return Ok(value);
+    };
+    let Value::String(parent, _) = parent else {
+        bail!("`{EXTENDS_KEY}` in {} must be a path", path.display());
+    };
+    let parent = path
+        .parent()
+        .map(|dir| dir.join(&parent))
+        .unwrap_or_else(|| PathBuf::from(&parent));
+    let mut base = load_chain(&parent, seen)
+        .with_context(|| format!("failed to load base bindings for {}", path.display()))?;
+    merge(&mut base, value);
+    Ok(base)
+}
+
+/// Deep-merges `overlay` into `base`: mappings merge per key, everything else is replaced.
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => merge_mapping(base, overlay),
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn merge_mapping(base: &mut Mapping, overlay: Mapping) {
+    for (key, value) in overlay {
+        match base.get_mut(&key) {
+            Some(existing) => merge(existing, value),
+            None => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn interpolate(value: &mut Value, tenant: &str, secrets: Secrets<'_>) -> Result<()> {
+    match value {
+        Value::String(raw, _) => {
+            if let Some(name) = whole_env_placeholder(raw) {
+                let resolved = env_var(name)?;
+                *value = match serde_yaml::from_str::<Value>(&resolved) {
+                    Ok(parsed @ (Value::Bool(..) | Value::Number(..) | Value::Null(..))) => parsed,
+                    _ => Value::from(literal(&resolved, secrets)),
+                };
+            } else if raw.contains('$') {
+                *raw = substitute(raw, tenant, secrets)?;
+            }
+        }
+        Value::Mapping(map) => {
+            for (key, value) in map.iter_mut() {
+                interpolate(value, tenant, secrets)
+                    .with_context(|| format!("in `{}`", describe_key(key)))?;
+            }
+        }
+        Value::Sequence(items) => {
+            for item in items.iter_mut() {
+                interpolate(item, tenant, secrets)?;
+            }
+        }
+        Value::Tagged(tagged) => interpolate(&mut tagged.value, tenant, secrets)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// `NAME` when `raw` is exactly `${NAME}` for an environment variable.
+fn whole_env_placeholder(raw: &str) -> Option<&str> {
+    let name = raw.strip_prefix("${")?.strip_suffix('}')?;
+    (!name.contains(['{', '}', '$', ':'])).then_some(name)
+}
+
+/// Substituted text, re-escaped when another interpolation pass will follow.
+fn literal(text: &str, secrets: Secrets<'_>) -> String {
+    match secrets {
+        Secrets::Resolve(_) | Secrets::Mocked(_) => text.to_string(),
+        Secrets::Keep => text.replace("${", "$${"),
+    }
+}
+
+fn substitute(raw: &str, tenant: &str, secrets: Secrets<'_>) -> Result<String> {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(after) = tail.strip_prefix("$${") {
+            out.push_str(&literal("${", secrets));
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("${") {
+            let end = after
+                .find('}')
+                .ok_or_else(|| anyhow!("unterminated placeholder in {raw:?}"))?;
+            let placeholder = &after[..end];
+            if matches!(secrets, Secrets::Keep) && placeholder.starts_with("secret:") {
+                out.push_str(&tail[..end + 3]);
+            } else {
+                out.push_str(&literal(
+                    &resolve_placeholder(placeholder, tenant, secrets)?,
+                    secrets,
+                ));
+            }
+            rest = &after[end + 1..];
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn resolve_placeholder(placeholder: &str, tenant: &str, secrets: Secrets<'_>) -> Result<String> {
+    match placeholder.split_once(':') {
+        Some(("secret", name)) if !name.trim().is_empty() => {
+            let name = name.trim();
+            match secrets {
+                Secrets::Mocked(mocks) => mocks
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("secret {name} is not mocked")),
+                Secrets::Resolve(policy) => {
+                    let granted = policy.granted(tenant).ok_or_else(|| {
+                        anyhow!(
+                            "secret {name} needs a secret grant for tenant {tenant} \
+                             (--allowed-secrets or --secret-grant)"
+                        )
+                    })?;
+                    if !granted.contains(name) {
+                        bail!("secret {name} is not granted to tenant {tenant}");
+                    }
+                    secrets::read_for_tenant_as("bindings", tenant, name)
+                }
+                Secrets::Keep => bail!("secret {name} cannot be resolved while rendering"),
+            }
+        }
+        Some((kind, _)) => bail!("unsupported placeholder `${{{placeholder}}}` ({kind})"),
+        None => env_var(placeholder),
+    }
+}
+
+fn env_var(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("empty placeholder `${{}}`");
+    }
+    if !name.starts_with(ENV_PREFIX) {
+        bail!("environment variable {name} is not readable from bindings (only {ENV_PREFIX}*)");
+    }
+    std::env::var(name).map_err(|_| anyhow!("environment variable {name} is not set"))
+}
+
+fn describe_key(key: &Value) -> String {
+    match key {
+        Value::String(key, _) => key.clone(),
+        other => serde_yaml::to_string(other)
+            .map(|key| key.trim().to_string())
+            .unwrap_or_else(|_| "?".into()),
+    }
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        file.write_all(contents)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+    #[cfg(not(unix))]
+    {
+        fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extends_merges_base_and_interpolates_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let base_dir = root.join("_base");
+        let tenant_dir = root.join("customera");
+        fs::create_dir_all(&base_dir).unwrap();
+        fs::create_dir_all(&tenant_dir).unwrap();
+        fs::write(
+            base_dir.join("bindings.yaml"),
+            "mcp:\n  store:\n    kind: local-dir\n    path: ./tools\n  retry:\n    max_attempts: 3\n    base_delay_ms: 250\nrate_limits:\n  messaging_send_qps: 10\n",
+        )
+        .unwrap();
+        let tenant = tenant_dir.join("bindings.yaml");
+        // SAFETY: the variables are unique to this test.
+        unsafe {
+            std::env::set_var("BINDINGS_TEST_QPS", "25");
+            std::env::set_var("BINDINGS_TEST_TOKEN", "s3cr3t");
+        }
+        fs::write(
+            &tenant,
+            "extends: ../_base/bindings.yaml\ntenant: customera\nmcp:\n  retry:\n    max_attempts: 5\nrate_limits:\n  messaging_send_qps: ${BINDINGS_TEST_QPS}\nnote: \"qps=${BINDINGS_TEST_QPS}, literal $${HOME}\"\ntoken: \"${secret:BINDINGS_TEST_TOKEN}\"\n",
+        )
+        .unwrap();
+
+        let policy = SecretPolicy::default()
+            .with_grant("customera", ["BINDINGS_TEST_TOKEN"])
+            .unwrap();
+        let value = resolve(&tenant, "customera", &policy).unwrap();
+        let ungranted = resolve(
+            &tenant,
+            "customera",
+            &SecretPolicy::default()
+                .with_grant("customera", ["OTHER"])
+                .unwrap(),
+        )
+        .unwrap_err();
+        let unrestricted = resolve(&tenant, "customera", &SecretPolicy::default()).unwrap_err();
+        let mocked = BTreeMap::from([("BINDINGS_TEST_TOKEN".to_string(), "mocked".to_string())]);
+        let with_mocks = resolve_with_secrets(&tenant, "customera", &mocked).unwrap();
+        let unmocked = resolve_with_secrets(&tenant, "customera", &BTreeMap::new()).unwrap_err();
+        let rendered = render(&tenant, "customera", &root.join(RESOLVED_DIR)).unwrap();
+        let plain = render(
+            &base_dir.join("bindings.yaml"),
+            "customera",
+            &root.join(RESOLVED_DIR),
+        )
+        .unwrap();
+        let written = fs::read_to_string(&rendered).unwrap();
+        let reresolved = resolve(&rendered, "customera", &policy).unwrap();
+        let config = host_config(&rendered, "customera", &policy).unwrap();
+
+        assert!(value.get(EXTENDS_KEY).is_none());
+        assert_eq!(value["mcp"]["retry"]["max_attempts"].as_u64(), Some(5));
+        assert_eq!(value["mcp"]["retry"]["base_delay_ms"].as_u64(), Some(250));
+        assert_eq!(value["mcp"]["store"]["path"].as_str(), Some("./tools"));
+        assert_eq!(
+            value["rate_limits"]["messaging_send_qps"].as_u64(),
+            Some(25)
+        );
+        assert_eq!(value["note"].as_str(), Some("qps=25, literal ${HOME}"));
+        assert_eq!(value["token"].as_str(), Some("s3cr3t"));
+        assert!(format!("{ungranted:#}").contains("not granted to tenant customera"));
+        assert!(format!("{unrestricted:#}").contains("needs a secret grant"));
+        assert_eq!(with_mocks["token"].as_str(), Some("mocked"));
+        assert!(format!("{unmocked:#}").contains("BINDINGS_TEST_TOKEN is not mocked"));
+        assert!(!written.contains("s3cr3t"));
+        assert!(written.contains("${secret:BINDINGS_TEST_TOKEN}"));
+        let tools = fs::canonicalize(&tenant_dir).unwrap().join("./tools");
+        assert_eq!(
+            reresolved["mcp"]["store"]["path"].as_str(),
+            Some(tools.to_str().unwrap())
+        );
+        let mut anchored = value.clone();
+        anchor_store_paths(&mut anchored, &fs::canonicalize(&tenant_dir).unwrap());
+        assert_eq!(reresolved, anchored);
+        assert_eq!(config.tenant, "customera");
+        assert_eq!(config.rate_limits.messaging_send_qps, 25);
+        assert!(rendered.starts_with(root.join(RESOLVED_DIR)));
+        assert_eq!(plain, base_dir.join("bindings.yaml"));
+    }
+
+    #[test]
+    fn cycles_and_missing_variables_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.yaml"), "extends: b.yaml\ntenant: a\n").unwrap();
+        fs::write(root.join("b.yaml"), "extends: a.yaml\n").unwrap();
+        fs::write(
+            root.join("c.yaml"),
+            "tenant: c\ntoken: ${BINDINGS_TEST_UNSET_VARIABLE}\n",
+        )
+        .unwrap();
+        fs::write(root.join("d.yaml"), "tenant: d\nhome: \"at ${HOME}\"\n").unwrap();
+        let policy = SecretPolicy::default();
+
+        let cycle = resolve(&root.join("a.yaml"), "a", &policy).unwrap_err();
+        let missing = resolve(&root.join("c.yaml"), "c", &policy).unwrap_err();
+        let outside = resolve(&root.join("d.yaml"), "d", &policy).unwrap_err();
+        let rendered = render(&root.join("d.yaml"), "d", &root.join(RESOLVED_DIR)).unwrap_err();
+
+        assert!(format!("{cycle:#}").contains("extend themselves"));
+        assert!(format!("{missing:#}").contains("BINDINGS_TEST_UNSET_VARIABLE is not set"));
+        assert!(format!("{outside:#}").contains("HOME is not readable from bindings"));
+        assert!(format!("{rendered:#}").contains("HOME is not readable from bindings"));
+    }
+}
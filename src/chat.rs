@@ -0,0 +1,294 @@
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::{Value, json};
+
+use crate::runner_bridge::RunnerBridge;
+use crate::types::{Activity, ActivityType, Attachment, ChannelAccount, ConversationAccount};
+
+const ADAPTIVE_CARD: &str = "application/vnd.microsoft.card.adaptive";
+const HERO_CARD: &str = "application/vnd.microsoft.card.hero";
+const THUMBNAIL_CARD: &str = "application/vnd.microsoft.card.thumbnail";
+
+/// One conversation with a tenant registered on a [`RunnerBridge`], without NATS.
+pub struct ChatSession {
+    runner: RunnerBridge,
+    tenant: String,
+    conversation: String,
+    turn: u64,
+}
+
+impl ChatSession {
+    pub fn new(
+        runner: RunnerBridge,
+        tenant: impl Into<String>,
+        conversation: impl Into<String>,
+    ) -> Self {
+        Self {
+            runner,
+            tenant: tenant.into(),
+            conversation: conversation.into(),
+            turn: 0,
+        }
+    }
+
+    pub fn tenant(&self) -> &str {
+        &self.tenant
+    }
+
+    pub fn conversation(&self) -> &str {
+        &self.conversation
+    }
+
+    /// Continues in a fresh conversation.
+    pub fn restart(&mut self, conversation: impl Into<String>) {
+        self.conversation = conversation.into();
+        self.turn = 0;
+    }
+
+    /// Wraps `text` into the next message activity; ids are `<conversation>:<turn>`.
+    pub fn activity(&mut self, text: &str) -> Activity {
+        self.turn += 1;
+        Activity {
+            activity_type: ActivityType::Message,
+            id: Some(format!("{}:{}", self.conversation, self.turn)),
+            timestamp: Some(Utc::now()),
+            channel_id: Some("greentic-demo-chat".into()),
+            from: Some(ChannelAccount {
+                id: Some("chat-user".into()),
+                name: None,
+            }),
+            recipient: Some(ChannelAccount {
+                id: Some(self.tenant.clone()),
+                name: None,
+            }),
+            conversation: Some(ConversationAccount {
+                id: Some(self.conversation.clone()),
+                name: None,
+            }),
+            text: Some(text.to_string()),
+            channel_data: Some(json!({ "tenant": self.tenant })),
+            ..Activity::default()
+        }
+    }
+
+    /// Runs `text` through the tenant's messaging flow and returns the outgoing activities.
+    pub async fn send(&mut self, text: &str) -> Result<Vec<Activity>> {
+        let activity = self.activity(text);
+        self.runner.handle_activity(&self.tenant, activity).await
+    }
+}
+
+/// Human-readable lines for an outgoing activity: its text, then one line per attachment
+/// (cards are reduced to their text and button titles).
+pub fn render(activity: &Activity) -> Vec<String> {
+    let mut lines = Vec::new();
+    match &activity.activity_type {
+        ActivityType::Message => {}
+        ActivityType::Typing => lines.push("[typing]".to_string()),
+        other => {
+            let mut line = match activity.name.as_deref() {
+                Some(name) => format!("[{other} {name}]"),
+                None => format!("[{other}]"),
+            };
+            if let Some(value) = &activity.value {
+                line.push_str(&format!(" {value}"));
+            }
+            lines.push(line);
+        }
+    }
+    if let Some(text) = activity.text.as_deref().filter(|text| !text.is_empty()) {
+        lines.push(text.to_string());
+    }
+    for attachment in activity.attachments.iter().flatten() {
+        lines.push(render_attachment(attachment));
+    }
+    lines
+}
+
+fn render_attachment(attachment: &Attachment) -> String {
+    let content = &attachment.content;
+    let (label, mut parts, buttons) = match attachment.content_type.as_str() {
+        ADAPTIVE_CARD => {
+            let mut parts = Vec::new();
+            if let Some(body) = content.get("body") {
+                adaptive_text(body, &mut parts);
+            }
+            ("adaptive card", parts, titles(content.get("actions")))
+        }
+        HERO_CARD | THUMBNAIL_CARD => {
+            let parts = ["title", "subtitle", "text"]
+                .iter()
+                .filter_map(|key| content.get(*key).and_then(Value::as_str))
+                .filter(|text| !text.is_empty())
+                .map(str::to_string)
+                .collect();
+            let label = if attachment.content_type == HERO_CARD {
+                "hero card"
+            } else {
+                "thumbnail card"
+            };
+            (label, parts, titles(content.get("buttons")))
+        }
+        other => {
+            return match attachment.name.as_deref().or(content.as_str()) {
+                Some(target) => format!("[attachment {other}] {target}"),
+                None => format!("[attachment {other}]"),
+            };
+        }
+    };
+    if parts.is_empty() {
+        parts.extend(attachment.name.clone());
+    }
+    let mut line = format!("[{label}] {}", parts.join(" | "));
+    for button in buttons {
+        line.push_str(&format!(" [{button}]"));
+    }
+    line.trim_end().to_string()
+}
+
+/// Text of `TextBlock`, `RichTextBlock` and `FactSet` elements, depth first.
+fn adaptive_text(elements: &Value, parts: &mut Vec<String>) {
+    let Some(elements) = elements.as_array() else {
+        return;
+    };
+    for element in elements {
+        match element.get("type").and_then(Value::as_str) {
+            Some("TextBlock") | Some("TextRun") => {
+                if let Some(text) = element.get("text").and_then(Value::as_str) {
+                    parts.push(text.to_string());
+                }
+            }
+            Some("RichTextBlock") => adaptive_text(&element["inlines"], parts),
+            Some("FactSet") => {
+                for fact in element["facts"].as_array().into_iter().flatten() {
+                    let title = fact["title"].as_str().unwrap_or_default();
+                    let value = fact["value"].as_str().unwrap_or_default();
+                    parts.push(format!("{title}: {value}"));
+                }
+            }
+            Some("ColumnSet") => {
+                for column in element["columns"].as_array().into_iter().flatten() {
+                    adaptive_text(&column["items"], parts);
+                }
+            }
+            _ => adaptive_text(&element["items"], parts),
+        }
+    }
+}
+
+fn titles(actions: Option<&Value>) -> Vec<String> {
+    actions
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|action| action.get("title").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Mode;
+    use crate::secret_policy::SecretPolicy;
+
+    #[test]
+    fn activities_share_the_conversation_and_cards_render_as_summaries() {
+        let mut session = ChatSession::new(
+            RunnerBridge::new(Mode::Dev, SecretPolicy::default()),
+            "customera",
+            "chat-1",
+        );
+        let first = session.activity("hi");
+        let second = session.activity("again");
+        assert_eq!(first.id.as_deref(), Some("chat-1:1"));
+        assert_eq!(second.id.as_deref(), Some("chat-1:2"));
+        assert_eq!(
+            second.conversation.and_then(|conversation| conversation.id),
+            Some("chat-1".to_string())
+        );
+        assert_eq!(second.channel_data, Some(json!({ "tenant": "customera" })));
+
+        let reply = Activity {
+            text: Some("Pick a plan".into()),
+            attachments: Some(vec![
+                Attachment {
+                    content_type: ADAPTIVE_CARD.into(),
+                    content: json!({
+                        "type": "AdaptiveCard",
+                        "body": [
+                            { "type": "TextBlock", "text": "Plans" },
+                            { "type": "Container", "items": [
+                                { "type": "FactSet", "facts": [{ "title": "Basic", "value": "$5" }] }
+                            ]},
+                            { "type": "Image", "url": "https://example.com/a.png" }
+                        ],
+                        "actions": [
+                            { "type": "Action.Submit", "title": "Basic" },
+                            { "type": "Action.OpenUrl", "title": "Compare" }
+                        ]
+                    }),
+                    name: None,
+                },
+                Attachment {
+                    content_type: HERO_CARD.into(),
+                    content: json!({ "title": "Welcome", "text": "Start here", "buttons": [{ "title": "Go" }] }),
+                    name: None,
+                },
+                Attachment {
+                    content_type: "image/png".into(),
+                    content: json!("https://example.com/logo.png"),
+                    name: None,
+                },
+            ]),
+            ..Activity::default()
+        };
+        assert_eq!(
+            render(&reply),
+            vec![
+                "Pick a plan",
+                "[adaptive card] Plans | Basic: $5 [Basic] [Compare]",
+                "[hero card] Welcome | Start here [Go]",
+                "[attachment image/png] https://example.com/logo.png",
+            ]
+        );
+
+        let typing = Activity {
+            activity_type: ActivityType::Typing,
+            ..Activity::default()
+        };
+        assert_eq!(render(&typing), vec!["[typing]"]);
+    }
+
+    #[tokio::test]
+    async fn repo_tenant_replies_in_the_chat_conversation() {
+        let dir = tempfile::tempdir().unwrap();
+        let packs = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("packs");
+        let report = crate::loader::load_packs(
+            &packs,
+            &dir.path().join("cache"),
+            &crate::pack_signing::PackVerifier::default(),
+        )
+        .unwrap();
+        let runner = RunnerBridge::new(Mode::Dev, SecretPolicy::default());
+        for pack in &report.tenants {
+            runner.register_pack(pack).await.unwrap();
+        }
+
+        let mut session = ChatSession::new(runner, "customerb", "chat-e2e");
+        let replies = session.send("hello there").await.unwrap();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(
+            render(&replies[0]),
+            vec!["Customer B received: hello there"]
+        );
+        assert_eq!(replies[0].reply_to_id.as_deref(), Some("chat-e2e:1"));
+        assert_eq!(
+            replies[0]
+                .conversation
+                .as_ref()
+                .and_then(|conversation| conversation.id.as_deref()),
+            Some("chat-e2e")
+        );
+    }
+}
@@ -0,0 +1,356 @@
+use std::fmt;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::config::{AppConfig, Mode, NatsAuth, TelemetryConfig};
+use crate::pack_signing::PackVerifier;
+
+/// Every problem found in an [`AppConfig`], so a deployment can be fixed in one pass.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigReport {
+    pub ok: bool,
+    pub issues: Vec<ConfigIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    /// Setting the issue is about, keyed like `config print` (`nats.jwt`, `subjects.prefix`).
+    pub key: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+impl ConfigReport {
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    fn error(&mut self, key: &'static str, message: impl Into<String>) {
+        self.push(Severity::Error, key, message);
+    }
+
+    fn warning(&mut self, key: &'static str, message: impl Into<String>) {
+        self.push(Severity::Warning, key, message);
+    }
+
+    /// An error in prod, a warning in dev.
+    fn strict(&mut self, mode: &Mode, key: &'static str, message: impl Into<String>) {
+        match mode {
+            Mode::Prod => self.error(key, message),
+            Mode::Dev => self.warning(key, message),
+        }
+    }
+
+    fn push(&mut self, severity: Severity, key: &'static str, message: impl Into<String>) {
+        self.issues.push(ConfigIssue {
+            severity,
+            key,
+            message: message.into(),
+        });
+    }
+
+    fn finish(mut self) -> Self {
+        self.issues.sort_by_key(|issue| issue.severity);
+        let ok = self.errors().next().is_none();
+        self.ok = ok;
+        self
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .issues
+            .iter()
+            .map(|issue| issue.key.len())
+            .max()
+            .unwrap_or(0)
+            .max("SETTING".len());
+        if !self.issues.is_empty() {
+            writeln!(f, "{:<8} {:<width$}  PROBLEM", "SEVERITY", "SETTING")?;
+        }
+        for issue in &self.issues {
+            writeln!(
+                f,
+                "{:<8} {:<width$}  {}",
+                issue.severity.as_str(),
+                issue.key,
+                issue.message
+            )?;
+        }
+        write!(
+            f,
+            "{} error(s), {} warning(s)",
+            self.errors().count(),
+            self.warnings().count()
+        )
+    }
+}
+
+impl std::error::Error for ConfigReport {}
+
+impl AppConfig {
+    /// Checks the whole configuration and collects every error and warning.
+    pub fn check(&self) -> ConfigReport {
+        let mut report = ConfigReport::default();
+        let mode = &self.mode;
+
+        for warning in &self.warnings {
+            report.warning("startup", warning.clone());
+        }
+        for (key, message) in &self.secret_errors {
+            report.error(key, message.clone());
+        }
+        let resolved = |key| !self.secret_errors.contains_key(key);
+
+        match &self.nats.auth {
+            NatsAuth::Jwt { jwt, seed } => {
+                if jwt.trim().is_empty() && resolved("nats.jwt") {
+                    report.error(
+                        "nats.jwt",
+                        "NATS_JWT is empty; prod mode requires a valid JWT",
+                    );
+                }
+                if !resolved("nats.seed") {
+                    // Already reported above.
+                } else if seed.trim().is_empty() {
+                    report.error(
+                        "nats.seed",
+                        "NATS_SEED is empty; prod mode requires a valid seed",
+                    );
+                } else if let Err(err) = nkeys::KeyPair::from_seed(seed.trim()) {
+                    report.error("nats.seed", format!("NATS seed is not a valid nkey: {err}"));
+                }
+            }
+            NatsAuth::None if *mode == Mode::Prod => {
+                report.error("nats.auth", "prod mode requires JWT-based NATS credentials");
+            }
+            NatsAuth::None => {}
+        }
+        let servers = self.nats.url.split(',').map(str::trim);
+        for server in servers.filter(|_| resolved("nats.url")) {
+            if let Err(message) = check_server(server) {
+                report.error("nats.url", message);
+            }
+        }
+
+        if let Err(message) = check_subject(self.subjects.prefix()) {
+            report.error("subjects.prefix", message);
+        }
+        if let Some(subject) = &self.secrets_audit_subject
+            && let Err(message) = check_subject(subject)
+        {
+            report.error("subjects.secrets_audit", message);
+        }
+
+        if let TelemetryConfig::Preconfigured { payload, source } = &self.telemetry
+            && let Err(err) = serde_json::from_str::<serde_json::Value>(payload)
+        {
+            report.strict(
+                mode,
+                "telemetry.config",
+                format!(
+                    "telemetry config from {} is not valid JSON: {err}",
+                    source.as_str()
+                ),
+            );
+        }
+
+        match &self.pack_index {
+            Some(index) if !is_remote(index) && !Path::new(index).is_file() => {
+                report.error(
+                    "tenants.pack_index",
+                    format!("pack index {index} does not exist"),
+                );
+            }
+            Some(_) => {}
+            None if !self.packs_dir.is_dir() => report.error(
+                "tenants.packs_dir",
+                format!(
+                    "packs directory {} does not exist or is not a directory",
+                    self.packs_dir.display()
+                ),
+            ),
+            None => {}
+        }
+        if let Err(err) = PackVerifier::from_key(self.pack_public_key.as_deref()) {
+            report.error("tenants.public_key", format!("{err:#}"));
+        }
+        if self.secret_policy.allowed().is_empty() {
+            report.strict(
+                mode,
+                "tenants.allowed_secrets",
+                "no --allowed-secrets / RUNNER_ALLOWED_SECRETS; tenants may declare any secret",
+            );
+        }
+
+        if self.limits.max_activity_bytes == 0 {
+            report.error(
+                "limits.max_activity_bytes",
+                "must be greater than zero; every activity would be dropped",
+            );
+        }
+
+        report.finish()
+    }
+}
+
+fn is_remote(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+/// Accepts `scheme://host[:port]` with a NATS scheme, or a bare `host:port`.
+fn check_server(server: &str) -> Result<(), String> {
+    if server.is_empty() {
+        return Err("NATS URL is empty".into());
+    }
+    let candidate = if server.contains("://") {
+        server.to_string()
+    } else {
+        format!("nats://{server}")
+    };
+    let url =
+        url::Url::parse(&candidate).map_err(|err| format!("invalid NATS URL {server}: {err}"))?;
+    if !matches!(url.scheme(), "nats" | "tls" | "ws" | "wss") {
+        return Err(format!(
+            "NATS URL {server} has unsupported scheme `{}` (expected nats, tls, ws or wss)",
+            url.scheme()
+        ));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(format!("NATS URL {server} has no host"));
+    }
+    Ok(())
+}
+
+/// A literal subject: dot-separated non-empty tokens without wildcards or whitespace.
+fn check_subject(subject: &str) -> Result<(), String> {
+    if subject.is_empty() {
+        return Err("subject is empty".into());
+    }
+    for token in subject.split('.') {
+        if token.is_empty() {
+            return Err(format!("subject `{subject}` has an empty token"));
+        }
+        if let Some(ch) = token
+            .chars()
+            .find(|ch| ch.is_whitespace() || *ch == '*' || *ch == '>')
+        {
+            return Err(format!(
+                "subject `{subject}` contains `{}`; wildcards and whitespace are not allowed",
+                ch.escape_default()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::config::{LimitsConfig, LoggingConfig, NatsConfig, SubjectConfig, TelemetrySource};
+    use crate::secret_policy::SecretPolicy;
+
+    #[test]
+    fn every_problem_is_reported_at_once() {
+        let config = AppConfig {
+            mode: Mode::Prod,
+            packs_dir: PathBuf::from("/nonexistent/greentic-packs"),
+            pack_index: None,
+            pack_cache_dir: PathBuf::from(".packs"),
+            nats: NatsConfig {
+                url: "nats://127.0.0.1:4222".into(),
+                auth: NatsAuth::Jwt {
+                    jwt: " ".into(),
+                    seed: "".into(),
+                },
+            },
+            logging: LoggingConfig::Telemetry,
+            subjects: SubjectConfig::new("messaging.*.activities"),
+            telemetry: TelemetryConfig::Preconfigured {
+                payload: "{not json".into(),
+                source: TelemetrySource::Env,
+            },
+            warnings: Vec::new(),
+            secret_policy: SecretPolicy::default(),
+            flow_audit_log: None,
+            secrets_audit_subject: None,
+            pack_public_key: None,
+            strict_packs: false,
+            limits: LimitsConfig {
+                max_activity_bytes: 1024,
+            },
+            config_file: None,
+            sources: BTreeMap::new(),
+            secret_errors: BTreeMap::from([(
+                "nats.seed",
+                "NATS_SEED secret not found; retrieve via greentic-secrets".to_string(),
+            )]),
+        };
+
+        let report = config.check();
+        assert!(!report.ok);
+        let keys: Vec<_> = report.errors().map(|issue| issue.key).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "nats.seed",
+                "nats.jwt",
+                "subjects.prefix",
+                "telemetry.config",
+                "tenants.packs_dir",
+                "tenants.allowed_secrets",
+            ]
+        );
+        let table = report.to_string();
+        assert!(table.starts_with("SEVERITY"));
+        assert!(table.ends_with("6 error(s), 0 warning(s)"));
+
+        let dev = AppConfig {
+            mode: Mode::Dev,
+            nats: NatsConfig {
+                url: "127.0.0.1:4222".into(),
+                auth: NatsAuth::None,
+            },
+            subjects: SubjectConfig::new("messaging.activities"),
+            telemetry: TelemetryConfig::Disabled,
+            packs_dir: std::env::temp_dir(),
+            secret_errors: BTreeMap::new(),
+            ..config
+        };
+        let report = dev.check();
+        assert!(report.ok);
+        assert_eq!(
+            report.warnings().map(|issue| issue.key).collect::<Vec<_>>(),
+            vec!["tenants.allowed_secrets"]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_yaml_bw as serde_yaml;
+
+/// Config file picked up from the working directory when `--config` is not given.
+pub const DEFAULT_CONFIG_FILE: &str = "greentic-demo.yaml";
+
+/// Optional bridge settings file (`greentic-demo.yaml`); relative paths resolve against it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Same as `--dev`.
+    pub dev: Option<bool>,
+    pub nats: NatsSection,
+    pub subjects: SubjectsSection,
+    pub logging: LoggingSection,
+    pub telemetry: TelemetrySection,
+    pub tenants: TenantsSection,
+    pub limits: LimitsSection,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NatsSection {
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SubjectsSection {
+    pub prefix: Option<String>,
+    /// Subject receiving secret access audit events.
+    pub secrets_audit: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingSection {
+    /// Log file used in dev mode.
+    pub file: Option<PathBuf>,
+    pub flow_audit_log: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetrySection {
+    /// Same as `GREENTIC_TELEMETRY_CONFIG_FILE`.
+    pub config_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TenantsSection {
+    pub packs_dir: Option<PathBuf>,
+    pub pack_index: Option<String>,
+    pub pack_cache_dir: Option<PathBuf>,
+    pub strict: Option<bool>,
+    pub public_key: Option<String>,
+    pub allowed_secrets: Option<Vec<String>>,
+    /// Per-tenant secret grants, tenant name to secret names.
+    pub secret_grants: Option<BTreeMap<String, Vec<String>>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsSection {
+    pub max_activity_bytes: Option<usize>,
+}
+
+/// A parsed config file and where it was read from.
+#[derive(Debug, Clone)]
+pub struct LoadedConfigFile {
+    pub path: PathBuf,
+    pub file: ConfigFile,
+}
+
+impl LoadedConfigFile {
+    /// Reads `explicit` (which must exist), or `greentic-demo.yaml` in the working
+    /// directory when present.
+    pub fn discover(explicit: Option<&Path>) -> Result<Option<Self>> {
+        let path = match explicit {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let path = PathBuf::from(DEFAULT_CONFIG_FILE);
+                if !path.is_file() {
+                    return Ok(None);
+                }
+                path
+            }
+        };
+        Self::load(&path).map(Some)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        if contents.trim().is_empty() {
+            bail!("config file {} is empty", path.display());
+        }
+        let file = serde_yaml::from_str(&contents)
+            .with_context(|| format!("invalid config file {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    /// `path` as written in the file, made relative to the file's directory.
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        match self.path.parent() {
+            Some(dir) if path.is_relative() && !dir.as_os_str().is_empty() => dir.join(path),
+            _ => path.to_path_buf(),
+        }
+    }
+}
+
+/// Where an effective configuration value came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
+pub enum ValueSource {
+    Default,
+    File(PathBuf),
+    Env(String),
+    Cli(String),
+    Secret(String),
+}
+
+impl fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueSource::Default => f.write_str("default"),
+            ValueSource::File(path) => write!(f, "file {}", path.display()),
+            ValueSource::Env(name) => write!(f, "env {name}"),
+            ValueSource::Cli(flag) => write!(f, "cli {flag}"),
+            ValueSource::Secret(name) => write!(f, "secret {name}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_files_resolve_paths_and_reject_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let path = root.join(DEFAULT_CONFIG_FILE);
+        fs::write(
+            &path,
+            "nats:\n  url: nats://nats:4222\ntenants:\n  packs_dir: packs\n  secret_grants:\n    customera: [TELEGRAM_BOT_TOKEN]\nlimits:\n  max_activity_bytes: 4096\n",
+        )
+        .unwrap();
+        let typo = root.join("typo.yaml");
+        fs::write(&typo, "nats:\n  uri: nats://nats:4222\n").unwrap();
+
+        let loaded = LoadedConfigFile::discover(Some(&path)).unwrap().unwrap();
+        let err = LoadedConfigFile::load(&typo).unwrap_err();
+        let missing = LoadedConfigFile::discover(Some(&root.join("missing.yaml")));
+
+        assert_eq!(loaded.file.nats.url.as_deref(), Some("nats://nats:4222"));
+        assert_eq!(loaded.file.limits.max_activity_bytes, Some(4096));
+        assert_eq!(
+            loaded.resolve(loaded.file.tenants.packs_dir.as_deref().unwrap()),
+            root.join("packs")
+        );
+        assert!(format!("{err:#}").contains("unknown field `uri`"));
+        assert!(missing.is_err());
+    }
+}
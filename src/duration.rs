@@ -0,0 +1,145 @@
+use std::env;
+use std::time::Duration;
+
+use thiserror::Error;
+
+/// Why a duration string was rejected by [`parse_duration`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DurationError {
+    #[error("duration is empty")]
+    Empty,
+    #[error("invalid duration `{input}`: expected a number at `{rest}`")]
+    MissingNumber { input: String, rest: String },
+    #[error("invalid duration `{input}`: `{number}` needs a unit (ms, s, m, h, d)")]
+    MissingUnit { input: String, number: String },
+    #[error("invalid duration `{input}`: unknown unit `{unit}` (expected ms, s, m, h, d)")]
+    UnknownUnit { input: String, unit: String },
+    #[error(
+        "invalid duration `{input}`: fractions are not supported; use a smaller unit (e.g. 90m)"
+    )]
+    Fraction { input: String },
+    #[error("duration `{input}` is too large")]
+    Overflow { input: String },
+}
+
+/// Parses `90`, `500ms`, `30s`, `5m`, `2h`, `1d` and compounds such as `1m30s`; a bare
+/// integer means seconds.
+pub fn parse_duration(raw: &str) -> Result<Duration, DurationError> {
+    let input = raw.trim();
+    if input.is_empty() {
+        return Err(DurationError::Empty);
+    }
+    if let Ok(secs) = input.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let overflow = || DurationError::Overflow {
+        input: input.to_string(),
+    };
+    let mut total = Duration::ZERO;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 {
+            return Err(DurationError::MissingNumber {
+                input: input.to_string(),
+                rest: rest.to_string(),
+            });
+        }
+        let (number, tail) = rest.split_at(digits);
+        if tail.starts_with(['.', ',']) {
+            return Err(DurationError::Fraction {
+                input: input.to_string(),
+            });
+        }
+        let tail = tail.trim_start();
+        let letters = tail.len()
+            - tail
+                .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+                .len();
+        let (unit, tail) = tail.split_at(letters);
+        let value: u64 = number.parse().map_err(|_| overflow())?;
+        let part = match unit.to_ascii_lowercase().as_str() {
+            "" => {
+                return Err(DurationError::MissingUnit {
+                    input: input.to_string(),
+                    number: number.to_string(),
+                });
+            }
+            "ms" => Some(Duration::from_millis(value)),
+            "s" => Some(Duration::from_secs(value)),
+            "m" => value.checked_mul(60).map(Duration::from_secs),
+            "h" => value.checked_mul(60 * 60).map(Duration::from_secs),
+            "d" => value.checked_mul(24 * 60 * 60).map(Duration::from_secs),
+            _ => {
+                return Err(DurationError::UnknownUnit {
+                    input: input.to_string(),
+                    unit: unit.to_string(),
+                });
+            }
+        };
+        total = part
+            .and_then(|part| total.checked_add(part))
+            .ok_or_else(overflow)?;
+        rest = tail.trim_start();
+    }
+    Ok(total)
+}
+
+/// Reads `name` as a duration; `Ok(None)` when it is unset or blank.
+pub fn env_duration(name: &str) -> anyhow::Result<Option<Duration>> {
+    match env::var(name).ok().filter(|raw| !raw.trim().is_empty()) {
+        Some(raw) => parse_duration(&raw)
+            .map(Some)
+            .map_err(|err| anyhow::Error::new(err).context(format!("invalid {name}"))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_units_and_compounds_and_rejects_garbage() {
+        let cases = [
+            ("45", 45_000),
+            ("250ms", 250),
+            ("30s", 30_000),
+            ("5m", 300_000),
+            ("1m30s", 90_000),
+            ("1h 15m", 4_500_000),
+            ("2H", 7_200_000),
+            ("1d", 86_400_000),
+        ];
+        for (raw, millis) in cases {
+            assert_eq!(
+                parse_duration(raw).unwrap(),
+                Duration::from_millis(millis),
+                "{raw}"
+            );
+        }
+
+        assert_eq!(parse_duration(" "), Err(DurationError::Empty));
+        assert!(matches!(
+            parse_duration("5 minutes"),
+            Err(DurationError::UnknownUnit { unit, .. }) if unit == "minutes"
+        ));
+        assert!(matches!(
+            parse_duration("1m30"),
+            Err(DurationError::MissingUnit { number, .. }) if number == "30"
+        ));
+        assert!(matches!(
+            parse_duration("1.5h"),
+            Err(DurationError::Fraction { .. })
+        ));
+        assert!(matches!(
+            parse_duration("-5s"),
+            Err(DurationError::MissingNumber { .. })
+        ));
+        assert!(matches!(
+            parse_duration("99999999999999999999d"),
+            Err(DurationError::Overflow { .. })
+        ));
+    }
+}
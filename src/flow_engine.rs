@@ -0,0 +1,347 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, anyhow, bail};
+use greentic_flow::ir::{FlowIR, NodeIR};
+use greentic_runner_host::runner::engine::{FlowContext, NodeEvent};
+use handlebars::Handlebars;
+use serde::Deserialize;
+use serde_json::{Map as JsonMap, Value, json};
+
+use crate::gtpack::pack_flows;
+
+/// A tenant's flows, executed with greentic-runner-host 0.4 node semantics.
+///
+/// The 0.4 `FlowEngine` rebuilds flow IR from pack archives without node components, so it
+/// cannot run any pack; this engine keeps the IR greentic-flow's loader produces. Supported
+/// components: `qa.process`, `templating.handlebars`, `emit.*`, `flow.call`, `session.wait`
+/// (execution stops there) and `mcp.exec` answered from the context's mocks.
+pub struct TenantFlows {
+    flows: Vec<FlowIR>,
+    conflicts: Vec<FlowConflict>,
+    templates: Handlebars<'static>,
+}
+
+/// A flow id defined by more than one pack of a tenant; the last pack in load order wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowConflict {
+    pub flow_id: String,
+    pub overridden: PathBuf,
+    pub winner: PathBuf,
+}
+
+impl TenantFlows {
+    /// Loads the flows of every pack in `paths`, main pack first.
+    pub fn load(tenant: &str, paths: &[PathBuf]) -> Result<Self> {
+        let mut flows: Vec<FlowIR> = Vec::new();
+        let mut sources: HashMap<String, usize> = HashMap::new();
+        let mut conflicts = Vec::new();
+        for (idx, path) in paths.iter().enumerate() {
+            let loaded = pack_flows(path).with_context(|| {
+                format!("failed to load flows from {} for {tenant}", path.display())
+            })?;
+            for flow in loaded {
+                let Some(previous) = sources.insert(flow.id.clone(), idx) else {
+                    flows.push(flow);
+                    continue;
+                };
+                tracing::warn!(
+                    tenant,
+                    flow = %flow.id,
+                    overridden = %paths[previous].display(),
+                    overlay = %path.display(),
+                    "overlay redefines flow"
+                );
+                conflicts.push(FlowConflict {
+                    flow_id: flow.id.clone(),
+                    overridden: paths[previous].clone(),
+                    winner: path.clone(),
+                });
+                if let Some(slot) = flows.iter_mut().find(|existing| existing.id == flow.id) {
+                    *slot = flow;
+                }
+            }
+        }
+
+        let mut templates = Handlebars::new();
+        templates.set_strict_mode(false);
+        Ok(Self {
+            flows,
+            conflicts,
+            templates,
+        })
+    }
+
+    pub fn flows(&self) -> &[FlowIR] {
+        &self.flows
+    }
+
+    pub fn conflicts(&self) -> &[FlowConflict] {
+        &self.conflicts
+    }
+
+    pub fn flow_by_id(&self, flow_id: &str) -> Option<&FlowIR> {
+        self.flows.iter().find(|flow| flow.id == flow_id)
+    }
+
+    pub fn flow_by_type(&self, flow_type: &str) -> Option<&FlowIR> {
+        self.flows.iter().find(|flow| flow.flow_type == flow_type)
+    }
+
+    /// Runs `ctx.flow_id` from `ctx.node_id` (or the flow's start node) and returns its
+    /// output: emitted payloads followed by the final payload, unless that was emitted too.
+    pub fn execute(&self, ctx: &FlowContext<'_>, input: Value) -> Result<Value> {
+        let flow = self
+            .flow_by_id(ctx.flow_id)
+            .ok_or_else(|| anyhow!("flow {} not found", ctx.flow_id))?;
+        let mut current = ctx
+            .node_id
+            .map(str::to_string)
+            .or_else(|| flow.start.clone())
+            .or_else(|| flow.nodes.keys().next().cloned())
+            .with_context(|| format!("flow {} has no start node", flow.id))?;
+        let mut state = ExecutionState::new(input);
+
+        loop {
+            let node = flow
+                .nodes
+                .get(&current)
+                .with_context(|| format!("node {current} not found in flow {}", flow.id))?;
+            let payload = self.resolve(&node.payload_expr, &state.context())?;
+            let event = NodeEvent {
+                context: ctx,
+                node_id: &current,
+                node,
+                payload: &payload,
+            };
+            if let Some(observer) = ctx.observer {
+                observer.on_node_start(&event);
+            }
+            let output = match self.dispatch(ctx, node, &mut state, payload.clone()) {
+                Ok(output) => {
+                    if let Some(observer) = ctx.observer {
+                        observer.on_node_end(&event, &output);
+                    }
+                    output
+                }
+                Err(err) => {
+                    if let Some(observer) = ctx.observer {
+                        observer.on_node_error(&event, err.as_ref());
+                    }
+                    return Err(err.context(format!(
+                        "node {current} ({}) failed in flow {}",
+                        node.component, flow.id
+                    )));
+                }
+            };
+            state.nodes.insert(current.clone(), output.clone());
+
+            if node.component == "session.wait" {
+                tracing::debug!(
+                    tenant = ctx.tenant,
+                    flow = %flow.id,
+                    node = %current,
+                    "flow paused; sessions are not resumed by the demo runner"
+                );
+                return Ok(state.finalize(None));
+            }
+
+            // An emit node's payload is already in the egress.
+            let last = (!node.component.starts_with("emit")).then_some(output);
+            let mut next = None;
+            for route in &node.routes {
+                if route.out || route.to.as_deref() == Some("out") {
+                    return Ok(state.finalize(last));
+                }
+                if let Some(to) = &route.to {
+                    next = Some(to.clone());
+                    break;
+                }
+            }
+            match next {
+                Some(node) => current = node,
+                None => return Ok(state.finalize(last)),
+            }
+        }
+    }
+
+    fn dispatch(
+        &self,
+        ctx: &FlowContext<'_>,
+        node: &NodeIR,
+        state: &mut ExecutionState,
+        payload: Value,
+    ) -> Result<Value> {
+        match node.component.as_str() {
+            "qa.process" | "session.wait" => Ok(payload),
+            "templating.handlebars" => self.render(state, payload),
+            "flow.call" => self.call(ctx, payload),
+            "mcp.exec" => call_tool(ctx, payload),
+            component if component.starts_with("emit") => {
+                state.egress.push(payload.clone());
+                Ok(payload)
+            }
+            other => bail!("unsupported node component: {other}"),
+        }
+    }
+
+    fn render(&self, state: &ExecutionState, payload: Value) -> Result<Value> {
+        #[derive(Deserialize)]
+        struct TemplatePayload {
+            template: String,
+            #[serde(default)]
+            partials: BTreeMap<String, String>,
+            #[serde(default)]
+            data: Value,
+        }
+
+        let payload: TemplatePayload = serde_json::from_value(payload)
+            .context("invalid payload for templating.handlebars node")?;
+        let mut context = state.context();
+        if !payload.data.is_null() {
+            let data = self.resolve(&payload.data, &context)?;
+            merge_values(&mut context, data);
+        }
+
+        let mut engine = self.templates.clone();
+        for (name, body) in &payload.partials {
+            engine
+                .register_template_string(name, body)
+                .with_context(|| format!("failed to register partial {name}"))?;
+        }
+        let rendered = engine
+            .render_template(&payload.template, &context)
+            .context("failed to render template")?;
+        Ok(json!({ "text": rendered }))
+    }
+
+    fn call(&self, ctx: &FlowContext<'_>, payload: Value) -> Result<Value> {
+        #[derive(Deserialize)]
+        struct FlowCallPayload {
+            #[serde(alias = "flow")]
+            flow_id: String,
+            #[serde(default)]
+            input: Value,
+        }
+
+        let call: FlowCallPayload =
+            serde_json::from_value(payload).context("invalid payload for flow.call node")?;
+        if call.flow_id.trim().is_empty() {
+            bail!("flow.call requires a non-empty flow_id");
+        }
+        let sub_ctx = FlowContext {
+            tenant: ctx.tenant,
+            flow_id: &call.flow_id,
+            node_id: None,
+            tool: ctx.tool,
+            action: Some("flow.call"),
+            session_id: ctx.session_id,
+            provider_id: ctx.provider_id,
+            retry_config: ctx.retry_config,
+            observer: ctx.observer,
+            mocks: ctx.mocks,
+        };
+        self.execute(&sub_ctx, call.input)
+            .with_context(|| format!("flow.call failed for {}", call.flow_id))
+    }
+
+    /// Renders every string containing `{{` in `value` against `context`.
+    fn resolve(&self, value: &Value, context: &Value) -> Result<Value> {
+        match value {
+            Value::String(text) if text.contains("{{") => self
+                .templates
+                .render_template(text, context)
+                .map(Value::String)
+                .with_context(|| format!("failed to render template: {text}")),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| self.resolve(item, context))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array),
+            Value::Object(map) => {
+                let mut resolved = JsonMap::new();
+                for (key, item) in map {
+                    resolved.insert(key.clone(), self.resolve(item, context)?);
+                }
+                Ok(Value::Object(resolved))
+            }
+            other => Ok(other.clone()),
+        }
+    }
+}
+
+/// `mcp.exec` without the runner's `mcp` feature: only mocked tools can answer.
+fn call_tool(ctx: &FlowContext<'_>, payload: Value) -> Result<Value> {
+    #[derive(Deserialize)]
+    struct McpPayload {
+        component: String,
+        action: String,
+    }
+
+    let call: McpPayload =
+        serde_json::from_value(payload).context("invalid payload for mcp.exec node")?;
+    match ctx
+        .mocks
+        .and_then(|mocks| mocks.tool_short_circuit(&call.component, &call.action))
+    {
+        Some(result) => result,
+        None => bail!(
+            "mcp.exec {}/{} has no mock; this build cannot run MCP tools",
+            call.component,
+            call.action
+        ),
+    }
+}
+
+struct ExecutionState {
+    input: Value,
+    nodes: HashMap<String, Value>,
+    egress: Vec<Value>,
+}
+
+impl ExecutionState {
+    fn new(input: Value) -> Self {
+        Self {
+            input,
+            nodes: HashMap::new(),
+            egress: Vec::new(),
+        }
+    }
+
+    /// Template context: `{ input, nodes: { <id>: { ok, payload, meta } } }`.
+    fn context(&self) -> Value {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|(id, payload)| {
+                let output = json!({ "ok": true, "payload": payload, "meta": Value::Null });
+                (id.clone(), output)
+            })
+            .collect::<JsonMap<_, _>>();
+        json!({ "input": self.input, "nodes": nodes })
+    }
+
+    fn finalize(self, final_payload: Option<Value>) -> Value {
+        if self.egress.is_empty() {
+            return final_payload.unwrap_or(Value::Null);
+        }
+        let mut emitted = self.egress;
+        match final_payload {
+            None | Some(Value::Null) => {}
+            Some(Value::Array(items)) => emitted.extend(items),
+            Some(other) => emitted.push(other),
+        }
+        Value::Array(emitted)
+    }
+}
+
+fn merge_values(target: &mut Value, addition: Value) {
+    match (target, addition) {
+        (Value::Object(target_map), Value::Object(add_map)) => {
+            for (key, value) in add_map {
+                merge_values(target_map.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (slot, value) => *slot = value,
+    }
+}
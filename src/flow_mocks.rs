@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow, bail};
+use greentic_runner_host::runner::mocks::{
+    HttpMock, HttpMockMode, MockLayer, MocksConfig, SecretsMock, ToolsMock,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use serde_yaml_bw as serde_yaml;
+use tempfile::TempDir;
+
+use crate::flow_engine::TenantFlows;
+
+/// Canned secrets, HTTP cassettes and tool/node responses for running a tenant's flows.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct MockSpec {
+    #[serde(default)]
+    pub tools: BTreeMap<String, BTreeMap<String, Value>>,
+    #[serde(default)]
+    pub nodes: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub secrets: BTreeMap<String, String>,
+    #[serde(default)]
+    pub http_cassettes: Option<PathBuf>,
+    #[serde(default)]
+    pub net_allowlist: Vec<String>,
+}
+
+impl MockSpec {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read mocks file {path:?}"))?;
+        let mut spec: MockSpec = serde_yaml::from_str(&contents)
+            .with_context(|| format!("failed to parse mocks file {path:?}"))?;
+        if let Some(cassettes) = spec.http_cassettes.take() {
+            let base = path.parent().unwrap_or_else(|| Path::new("."));
+            spec.http_cassettes = Some(base.join(cassettes));
+        }
+        Ok(spec)
+    }
+}
+
+/// A [`MockLayer`] plus the scratch directory holding materialized tool scripts.
+pub struct TenantMocks {
+    spec: MockSpec,
+    script_dir: PathBuf,
+    layer: Arc<MockLayer>,
+    _run_dir: TempDir,
+}
+
+impl TenantMocks {
+    /// Builds the mock layer for `tenant`, writing one script per tool response.
+    pub fn prepare(tenant: &str, spec: MockSpec) -> Result<Self> {
+        let run_dir = tempfile::Builder::new()
+            .prefix(&format!("greentic-demo-mocks-{tenant}-"))
+            .tempdir()
+            .with_context(|| format!("failed to create mock dir for {tenant}"))?;
+        let script_dir = run_dir.path().join("tools");
+        fs::create_dir_all(&script_dir)
+            .with_context(|| format!("failed to create mock script dir {script_dir:?}"))?;
+
+        for (tool, actions) in &spec.tools {
+            for (action, response) in actions {
+                write_script(&script_dir, tool, action, response)?;
+            }
+        }
+
+        let config = MocksConfig {
+            http: Some(HttpMock {
+                record_replay_dir: spec.http_cassettes.clone(),
+                mode: HttpMockMode::FailOnMiss,
+                rewrites: Vec::new(),
+            }),
+            secrets: Some(SecretsMock {
+                map: spec.secrets.clone(),
+            }),
+            mcp_tools: Some(ToolsMock {
+                directory: None,
+                script_dir: Some(script_dir.clone()),
+                short_circuit: true,
+            }),
+            net_allowlist: spec.net_allowlist.clone(),
+            ..MocksConfig::default()
+        };
+        let layer = Arc::new(
+            MockLayer::new(config, run_dir.path())
+                .with_context(|| format!("failed to build mock layer for {tenant}"))?,
+        );
+
+        Ok(Self {
+            spec,
+            script_dir,
+            layer,
+            _run_dir: run_dir,
+        })
+    }
+
+    pub fn layer(&self) -> &Arc<MockLayer> {
+        &self.layer
+    }
+
+    /// Values for the tenant's `${secret:NAME}` bindings placeholders.
+    pub fn secrets(&self) -> &BTreeMap<String, String> {
+        &self.spec.secrets
+    }
+
+    /// Maps node-keyed mocks onto the tool/action their `mcp.exec` node invokes.
+    pub fn bind_nodes(&self, flows: &TenantFlows) -> Result<()> {
+        let mut pending: BTreeMap<&str, &Value> = self
+            .spec
+            .nodes
+            .iter()
+            .map(|(node, value)| (node.as_str(), value))
+            .collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        for flow in flows.flows() {
+            for (node_id, node) in &flow.nodes {
+                let Some(response) = pending.remove(node_id.as_str()) else {
+                    continue;
+                };
+                if node.component != "mcp.exec" {
+                    bail!(
+                        "node {node_id} in flow {} uses {}; only mcp.exec nodes can be mocked",
+                        flow.id,
+                        node.component
+                    );
+                }
+                let (tool, action) = mcp_target(&node.payload_expr).ok_or_else(|| {
+                    anyhow!(
+                        "node {node_id} in flow {} has no literal component/action",
+                        flow.id
+                    )
+                })?;
+                write_script(&self.script_dir, tool, action, response)?;
+            }
+        }
+
+        if !pending.is_empty() {
+            let missing = pending.keys().copied().collect::<Vec<_>>().join(", ");
+            bail!("mocked nodes not found in any flow: {missing}");
+        }
+        Ok(())
+    }
+}
+
+fn mcp_target(payload: &Value) -> Option<(&str, &str)> {
+    let component = payload.get("component")?.as_str()?;
+    let action = payload.get("action")?.as_str()?;
+    Some((component, action))
+}
+
+/// Mirrors the runner's `<tool>__<action>.json` script naming.
+fn script_name(tool: &str, action: &str) -> String {
+    let sanitize = |value: &str| -> String {
+        value
+            .chars()
+            .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+            .collect()
+    };
+    format!("{}__{}.json", sanitize(tool), sanitize(action))
+}
+
+fn write_script(dir: &Path, tool: &str, action: &str, response: &Value) -> Result<()> {
+    let path = dir.join(script_name(tool, action));
+    let body = serde_json::to_vec_pretty(response)?;
+    fs::write(&path, body).with_context(|| format!("failed to write mock script {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tool_scripts_live_in_a_private_dir_removed_on_drop() {
+        let spec: MockSpec = serde_yaml::from_str(
+            "tools:\n  weather.api:\n    forecast: { text: sunny }\nsecrets:\n  TOKEN: test\n",
+        )
+        .unwrap();
+        let mocks = TenantMocks::prepare("customera", spec.clone()).unwrap();
+        let other = TenantMocks::prepare("customera", spec).unwrap();
+        assert_ne!(mocks.script_dir, other.script_dir);
+
+        let script = mocks.script_dir.join("weather_api__forecast.json");
+        let value: Value = serde_json::from_slice(&fs::read(&script).unwrap()).unwrap();
+        assert_eq!(value, json!({ "text": "sunny" }));
+        assert_eq!(
+            mocks
+                .layer()
+                .tool_short_circuit("weather.api", "forecast")
+                .unwrap()
+                .unwrap(),
+            json!({ "text": "sunny" })
+        );
+        assert_eq!(mocks.secrets()["TOKEN"], "test");
+
+        let run_dir = mocks.script_dir.parent().unwrap().to_path_buf();
+        drop(mocks);
+        assert!(!run_dir.exists());
+    }
+
+    #[test]
+    fn mcp_target_requires_literal_component_and_action() {
+        let payload = json!({ "component": "weather.api", "action": "forecast", "args": {} });
+        assert_eq!(mcp_target(&payload), Some(("weather.api", "forecast")));
+        assert_eq!(mcp_target(&json!({ "component": "weather.api" })), None);
+    }
+}
@@ -0,0 +1,318 @@
+use std::error::Error as StdError;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use greentic_runner_host::runner::engine::{ExecutionObserver, NodeEvent};
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::Span;
+use tracing::field::Empty;
+
+/// Append-only JSON Lines sink shared by every activity the bridge executes.
+#[derive(Clone)]
+pub struct FlowAuditLog {
+    file: Arc<Mutex<File>>,
+}
+
+impl FlowAuditLog {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create audit directory {parent:?}"))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("unable to open flow audit log at {path:?}"))?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    fn append(&self, record: &AuditRecord<'_>) {
+        let mut line = match serde_json::to_vec(record) {
+            Ok(line) => line,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to serialize flow audit record");
+                return;
+            }
+        };
+        line.push(b'\n');
+        if let Err(err) = self.file.lock().write_all(&line) {
+            tracing::warn!(error = %err, "failed to write flow audit record");
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AuditRecord<'a> {
+    ts: String,
+    tenant: &'a str,
+    activity_id: &'a str,
+    flow: &'a str,
+    node: &'a str,
+    component: &'a str,
+    event: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+struct OpenNode {
+    flow: String,
+    node: String,
+    component: String,
+    started: Instant,
+    span: Span,
+}
+
+/// Per-activity observer that turns node transitions into tracing spans and audit records.
+///
+/// Nodes still open when execution stops are closed by [`FlowObserver::finish`].
+pub struct FlowObserver {
+    tenant: String,
+    activity_id: String,
+    open: Mutex<Vec<OpenNode>>,
+    failed: Mutex<Option<(String, String)>>,
+    audit: Option<FlowAuditLog>,
+}
+
+impl FlowObserver {
+    pub fn new(tenant: &str, activity_id: &str, audit: Option<FlowAuditLog>) -> Self {
+        Self {
+            tenant: tenant.to_string(),
+            activity_id: activity_id.to_string(),
+            open: Mutex::new(Vec::new()),
+            failed: Mutex::new(None),
+            audit,
+        }
+    }
+
+    /// Closes every node still open and returns the innermost `(flow, node)` when execution failed.
+    pub fn finish(&self, error: Option<&anyhow::Error>) -> Option<(String, String)> {
+        let mut open = std::mem::take(&mut *self.open.lock());
+        let failed = match error {
+            Some(_) => self.failed.lock().take().or_else(|| {
+                open.last()
+                    .map(|node| (node.flow.clone(), node.node.clone()))
+            }),
+            None => None,
+        };
+        while let Some(node) = open.pop() {
+            self.close(node, error.map(|err| format!("{err:#}")));
+        }
+        failed
+    }
+
+    fn enter(&self, event: &NodeEvent<'_>) {
+        let flow = event.context.flow_id;
+        let mut open = self.open.lock();
+        if let Some(position) = open.iter().position(|node| node.flow == flow) {
+            let finished = open.split_off(position);
+            for node in finished.into_iter().rev() {
+                self.close(node, None);
+            }
+        }
+
+        let span = tracing::info_span!(
+            "flow.node",
+            tenant = %self.tenant,
+            activity_id = %self.activity_id,
+            flow = %flow,
+            node = %event.node_id,
+            component = %event.node.component,
+            duration_ms = Empty,
+            status = Empty,
+        );
+        span.in_scope(|| tracing::debug!("node entered"));
+        self.record(
+            flow,
+            event.node_id,
+            &event.node.component,
+            "enter",
+            None,
+            None,
+        );
+        open.push(OpenNode {
+            flow: flow.to_string(),
+            node: event.node_id.to_string(),
+            component: event.node.component.clone(),
+            started: Instant::now(),
+            span,
+        });
+    }
+
+    fn exit(&self, event: &NodeEvent<'_>, error: Option<String>) {
+        let mut open = self.open.lock();
+        let position = open
+            .iter()
+            .rposition(|node| node.flow == event.context.flow_id && node.node == event.node_id);
+        if let Some(position) = position {
+            let node = open.remove(position);
+            if error.is_some() {
+                // Keep the innermost failure; enclosing `flow.call` nodes fail after it.
+                self.failed
+                    .lock()
+                    .get_or_insert_with(|| (node.flow.clone(), node.node.clone()));
+            }
+            self.close(node, error);
+        }
+    }
+
+    fn close(&self, node: OpenNode, error: Option<String>) {
+        let elapsed = node.started.elapsed().as_millis();
+        node.span.record("duration_ms", elapsed as u64);
+        let _entered = node.span.enter();
+        match &error {
+            Some(err) => {
+                node.span.record("status", "error");
+                tracing::warn!(error = %err, "node failed");
+            }
+            None => {
+                node.span.record("status", "ok");
+                tracing::debug!("node exited");
+            }
+        }
+        let event = if error.is_some() { "error" } else { "exit" };
+        self.record(
+            &node.flow,
+            &node.node,
+            &node.component,
+            event,
+            Some(elapsed),
+            error,
+        );
+    }
+
+    fn record(
+        &self,
+        flow: &str,
+        node: &str,
+        component: &str,
+        event: &'static str,
+        duration_ms: Option<u128>,
+        error: Option<String>,
+    ) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        audit.append(&AuditRecord {
+            ts: Utc::now().to_rfc3339(),
+            tenant: &self.tenant,
+            activity_id: &self.activity_id,
+            flow,
+            node,
+            component,
+            event,
+            duration_ms,
+            error,
+        });
+    }
+}
+
+impl ExecutionObserver for FlowObserver {
+    fn on_node_start(&self, event: &NodeEvent<'_>) {
+        self.enter(event);
+    }
+
+    fn on_node_end(&self, event: &NodeEvent<'_>, _output: &Value) {
+        self.exit(event, None);
+    }
+
+    fn on_node_error(&self, event: &NodeEvent<'_>, error: &dyn StdError) {
+        self.exit(event, Some(error.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Mode;
+    use crate::loader::load_packs;
+    use crate::pack_signing::PackVerifier;
+    use crate::runner_bridge::RunnerBridge;
+    use crate::secret_policy::SecretPolicy;
+    use crate::types::Activity;
+
+    #[tokio::test]
+    async fn audit_log_records_every_node_of_a_repo_pack_and_pinpoints_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let packs = Path::new(env!("CARGO_MANIFEST_DIR")).join("packs");
+        let report =
+            load_packs(&packs, &dir.path().join("cache"), &PackVerifier::default()).unwrap();
+        let mut pack = report
+            .tenants
+            .iter()
+            .find(|pack| pack.tenant == "customera")
+            .unwrap()
+            .clone();
+        let path = dir.path().join("flow-audit.jsonl");
+        let bridge = RunnerBridge::new(Mode::Dev, SecretPolicy::default())
+            .with_flow_audit(&path)
+            .unwrap();
+        bridge.register_pack(&pack).await.unwrap();
+        let activity = Activity {
+            id: Some("act-1".into()),
+            text: Some("hi".into()),
+            ..Activity::default()
+        };
+        bridge.handle_activity("customera", activity).await.unwrap();
+
+        // An unmocked tool call fails at its node.
+        let overlay = dir.path().join("lookup.ygtc");
+        fs::write(
+            &overlay,
+            "id: customera-messaging\ntype: messaging\nnodes:\n  lookup:\n    mcp.exec:\n      component: crm.api\n      action: find\n    routing:\n      - out: true\n",
+        )
+        .unwrap();
+        pack.overlays.push(overlay);
+        bridge.register_pack(&pack).await.unwrap();
+        let activity = Activity {
+            id: Some("act-2".into()),
+            text: Some("hi".into()),
+            ..Activity::default()
+        };
+        let err = bridge
+            .handle_activity("customera", activity)
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("at customera-messaging/lookup"));
+
+        let records: Vec<Value> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let events: Vec<(&str, &str, &str)> = records
+            .iter()
+            .map(|r| {
+                (
+                    r["activity_id"].as_str().unwrap(),
+                    r["node"].as_str().unwrap(),
+                    r["event"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("act-1", "in", "enter"),
+                ("act-1", "in", "exit"),
+                ("act-1", "reply", "enter"),
+                ("act-1", "reply", "exit"),
+                ("act-2", "lookup", "enter"),
+                ("act-2", "lookup", "error"),
+            ]
+        );
+        assert_eq!(records[0]["component"], "templating.handlebars");
+        assert!(records[5]["error"].is_string());
+    }
+}
@@ -0,0 +1,414 @@
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use flate2::read::GzDecoder;
+use greentic_flow::flow_bundle::load_and_validate_bundle_with_ir;
+use greentic_flow::ir::FlowIR;
+use greentic_pack::reader::{SigningPolicy, open_pack};
+use serde::Deserialize;
+use serde_yaml_bw as serde_yaml;
+
+const MANIFEST: &str = "manifest.yaml";
+
+/// `manifest.yaml` of a source-layout `.gtpack` (see `examples/deployment/generic-deploy.gtpack`).
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct GtpackManifest {
+    pub id: String,
+    pub version: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub flows: Vec<ManifestFlow>,
+    #[serde(default)]
+    pub components: Vec<ManifestComponent>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ManifestFlow {
+    pub id: String,
+    pub file: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ManifestComponent {
+    pub id: String,
+    #[serde(default)]
+    pub version_req: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// A `.gtpack` resolved to something the runner can load.
+#[derive(Debug, Clone)]
+pub enum Gtpack {
+    /// Directory (possibly extracted from an archive) with `manifest.yaml`, `flows/`, `components/`.
+    Source {
+        root: PathBuf,
+        manifest: GtpackManifest,
+    },
+    /// Archive built by greentic-pack; its flows are read straight from the zip.
+    Native { archive: PathBuf },
+}
+
+impl Gtpack {
+    /// Opens a `.gtpack` directory, its `manifest.yaml`, or a zip/tar/tar.gz archive
+    /// (unpacked under `extract_dir`).
+    pub fn open(path: &Path, extract_dir: &Path) -> Result<Self> {
+        if path.is_dir() {
+            return Self::from_dir(path);
+        }
+        if path.file_name().and_then(|n| n.to_str()) == Some(MANIFEST) {
+            let root = path.parent().unwrap_or_else(|| Path::new("."));
+            return Self::from_dir(root);
+        }
+
+        let format = ArchiveFormat::sniff(path)?;
+        let stem = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("pack.gtpack");
+        let target = extract_dir.join(stem);
+        if target.exists() {
+            fs::remove_dir_all(&target)
+                .with_context(|| format!("failed to clear {}", target.display()))?;
+        }
+        fs::create_dir_all(&target)
+            .with_context(|| format!("failed to create {}", target.display()))?;
+        format.extract(path, &target)?;
+
+        match find_manifest_root(&target)? {
+            Some(root) => Self::from_dir(&root),
+            None if format == ArchiveFormat::Zip => {
+                let _ = fs::remove_dir_all(&target);
+                Ok(Gtpack::Native {
+                    archive: path.to_path_buf(),
+                })
+            }
+            None => bail!("{} does not contain a {MANIFEST}", path.display()),
+        }
+    }
+
+    fn from_dir(root: &Path) -> Result<Self> {
+        let manifest_path = root.join(MANIFEST);
+        let contents = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+        let manifest: GtpackManifest = serde_yaml::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+        let pack = Gtpack::Source {
+            root: root.to_path_buf(),
+            manifest,
+        };
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Checks that every declared flow file and local component is present inside the pack.
+    pub fn validate(&self) -> Result<()> {
+        let Gtpack::Source { root, manifest } = self else {
+            return Ok(());
+        };
+        let canonical_root = root
+            .canonicalize()
+            .with_context(|| format!("failed to resolve {}", root.display()))?;
+
+        let mut missing = Vec::new();
+        let mut escaping = Vec::new();
+        for flow in &manifest.flows {
+            match root.join(&flow.file).canonicalize() {
+                Ok(path) if !path.starts_with(&canonical_root) => {
+                    escaping.push(format!("flow {} ({})", flow.id, flow.file.display()))
+                }
+                Ok(path) if path.is_file() => {}
+                _ => missing.push(format!("flow {} ({})", flow.id, flow.file.display())),
+            }
+        }
+        for component in &manifest.components {
+            if component.source.as_deref().is_some_and(|s| s != "local") {
+                continue;
+            }
+            if Path::new(&component.id).components().count() != 1 {
+                escaping.push(format!("component {}", component.id));
+                continue;
+            }
+            let dir = root.join("components");
+            let found = ["wasm", "yaml", "yml"]
+                .iter()
+                .any(|ext| dir.join(format!("{}.{ext}", component.id)).is_file());
+            if !found {
+                missing.push(format!("component {}", component.id));
+            }
+        }
+
+        if !escaping.is_empty() {
+            bail!(
+                "pack {} at {} references files outside the pack: {}",
+                manifest.id,
+                root.display(),
+                escaping.join(", ")
+            );
+        }
+        if !missing.is_empty() {
+            bail!(
+                "pack {} at {} references missing files: {}",
+                manifest.id,
+                root.display(),
+                missing.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Flow IR of every flow the pack declares, with node components intact.
+    pub fn flows(&self) -> Result<Vec<FlowIR>> {
+        match self {
+            Gtpack::Source { root, manifest } => manifest
+                .flows
+                .iter()
+                .map(|flow| load_flow(&root.join(&flow.file)))
+                .collect(),
+            Gtpack::Native { archive } => archive_flows(archive),
+        }
+    }
+
+    /// The pack's `manifest.yaml` for source packs, the archive otherwise; see [`pack_flows`].
+    pub fn entry_path(&self) -> PathBuf {
+        match self {
+            Gtpack::Source { root, .. } => root.join(MANIFEST),
+            Gtpack::Native { archive } => archive.clone(),
+        }
+    }
+
+    /// Directory searched for `bindings.yaml` next to the pack contents.
+    pub fn root(&self) -> Option<&Path> {
+        match self {
+            Gtpack::Source { root, .. } => Some(root),
+            Gtpack::Native { .. } => None,
+        }
+    }
+
+    pub fn manifest(&self) -> Option<&GtpackManifest> {
+        match self {
+            Gtpack::Source { manifest, .. } => Some(manifest),
+            Gtpack::Native { .. } => None,
+        }
+    }
+}
+
+/// True for `*.gtpack` directories/archives and `manifest.yaml` files inside one.
+pub fn is_gtpack(path: &Path) -> bool {
+    let is_pack_name = |p: &Path| {
+        p.file_name().and_then(|n| n.to_str()).is_some_and(|name| {
+            name.ends_with(".gtpack")
+                || name.ends_with(".gtpack.zip")
+                || name.ends_with(".gtpack.tar")
+                || name.ends_with(".gtpack.tar.gz")
+                || name.ends_with(".gtpack.tgz")
+        })
+    };
+    if path.file_name().and_then(|n| n.to_str()) == Some(MANIFEST) {
+        return path.parent().is_some_and(is_pack_name);
+    }
+    is_pack_name(path)
+}
+
+/// Flow IR for every flow in the pack at `path`: a `.ygtc` file, a source pack's
+/// `manifest.yaml`, or a native runner archive.
+pub fn pack_flows(path: &Path) -> Result<Vec<FlowIR>> {
+    if path.file_name().and_then(|n| n.to_str()) == Some(MANIFEST) {
+        let root = path.parent().unwrap_or_else(|| Path::new("."));
+        return Gtpack::from_dir(root)?.flows();
+    }
+    if path.extension().and_then(|ext| ext.to_str()) == Some("ygtc") {
+        return Ok(vec![load_flow(path)?]);
+    }
+    archive_flows(path)
+}
+
+fn load_flow(path: &Path) -> Result<FlowIR> {
+    let yaml =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    load_and_validate_bundle_with_ir(&yaml, Some(path))
+        .map(|(_, ir)| ir)
+        .with_context(|| format!("{} is not a valid flow", path.display()))
+}
+
+/// Reads flow sources out of a native archive; the runner's own loader drops node components.
+fn archive_flows(path: &Path) -> Result<Vec<FlowIR>> {
+    let pack = open_pack(path, SigningPolicy::DevOk)
+        .map_err(|err| anyhow!("failed to open pack {}: {}", path.display(), err.message))?;
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))
+        .with_context(|| format!("{} is not a valid gtpack archive", path.display()))?;
+    pack.manifest
+        .flows
+        .iter()
+        .map(|entry| {
+            let mut yaml = String::new();
+            archive
+                .by_name(&entry.file_yaml)
+                .with_context(|| format!("{} is missing {}", path.display(), entry.file_yaml))?
+                .read_to_string(&mut yaml)
+                .with_context(|| format!("failed to read {}", entry.file_yaml))?;
+            load_and_validate_bundle_with_ir(&yaml, None)
+                .map(|(_, ir)| ir)
+                .with_context(|| format!("flow {} in {} is not valid", entry.id, path.display()))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    Zip,
+    TarGz,
+    Tar,
+}
+
+impl ArchiveFormat {
+    fn sniff(path: &Path) -> Result<Self> {
+        let mut header = [0u8; 262];
+        let mut file =
+            File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        let read = file.read(&mut header)?;
+        let header = &header[..read];
+        if header.starts_with(b"PK\x03\x04") {
+            Ok(ArchiveFormat::Zip)
+        } else if header.starts_with(&[0x1f, 0x8b]) {
+            Ok(ArchiveFormat::TarGz)
+        } else if header.len() >= 262 && &header[257..262] == b"ustar" {
+            Ok(ArchiveFormat::Tar)
+        } else {
+            bail!("{} is not a zip or tar .gtpack archive", path.display())
+        }
+    }
+
+    fn extract(self, archive: &Path, target: &Path) -> Result<()> {
+        let file =
+            File::open(archive).with_context(|| format!("failed to open {}", archive.display()))?;
+        let result = match self {
+            ArchiveFormat::Zip => zip::ZipArchive::new(BufReader::new(file))
+                .and_then(|mut zip| zip.extract(target))
+                .map_err(anyhow::Error::from),
+            ArchiveFormat::TarGz => tar::Archive::new(GzDecoder::new(BufReader::new(file)))
+                .unpack(target)
+                .map_err(anyhow::Error::from),
+            ArchiveFormat::Tar => tar::Archive::new(BufReader::new(file))
+                .unpack(target)
+                .map_err(anyhow::Error::from),
+        };
+        result.with_context(|| format!("failed to extract {}", archive.display()))
+    }
+}
+
+/// Archives may hold the pack at their root or inside a single top-level directory.
+pub(crate) fn find_manifest_root(dir: &Path) -> Result<Option<PathBuf>> {
+    if dir.join(MANIFEST).is_file() {
+        return Ok(Some(dir.to_path_buf()));
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && path.join(MANIFEST).is_file() {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use greentic_pack::builder::{PackBuilder, PackMeta, Signing};
+
+    use super::*;
+
+    fn example_pack() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/deployment/generic-deploy.gtpack")
+    }
+
+    #[test]
+    fn opens_example_deployment_pack() {
+        let dir = tempfile::tempdir().unwrap();
+        let pack = Gtpack::open(&example_pack(), dir.path()).unwrap();
+        let manifest = pack.manifest().unwrap();
+        assert_eq!(manifest.id, "greentic.demo.generic-deploy");
+        assert_eq!(manifest.flows.len(), 2);
+        assert!(is_gtpack(&pack.entry_path()));
+    }
+
+    #[test]
+    fn tar_gz_archive_is_extracted_and_validated() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let archive_path = root.join("deploy.gtpack");
+        {
+            let file = File::create(&archive_path).unwrap();
+            let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            let mut builder = tar::Builder::new(encoder);
+            builder
+                .append_dir_all("generic-deploy.gtpack", example_pack())
+                .unwrap();
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+
+        let pack = Gtpack::open(&archive_path, &root.join("extracted")).unwrap();
+        let entry = pack.entry_path();
+        assert!(entry.starts_with(root.join("extracted")));
+        assert!(entry.is_file());
+
+        let broken = root.join("broken");
+        fs::create_dir_all(&broken).unwrap();
+        fs::write(
+            broken.join(MANIFEST),
+            "id: broken\nversion: 0.1.0\nflows:\n  - id: missing\n    file: flows/missing.ygtc\n",
+        )
+        .unwrap();
+        let err = Gtpack::open(&broken, root).unwrap_err();
+        assert!(err.to_string().contains("flow missing"));
+    }
+
+    #[test]
+    fn flows_outside_the_pack_are_rejected_and_flows_keep_their_components() {
+        let root = tempfile::tempdir().unwrap();
+        let pack = root.path().join("escape.gtpack");
+        fs::create_dir_all(&pack).unwrap();
+        fs::write(root.path().join("outside.ygtc"), "").unwrap();
+        fs::write(
+            pack.join(MANIFEST),
+            "id: escape\nversion: 0.1.0\nflows:\n  - id: outside\n    file: ../outside.ygtc\n",
+        )
+        .unwrap();
+        let err = Gtpack::open(&pack, root.path()).unwrap_err();
+        assert!(err.to_string().contains("outside the pack"));
+
+        let flow = Path::new(env!("CARGO_MANIFEST_DIR")).join("packs/customera/index.ygtc");
+        let yaml = fs::read_to_string(&flow).unwrap();
+        let (bundle, _) = load_and_validate_bundle_with_ir(&yaml, Some(&flow)).unwrap();
+        let meta = PackMeta {
+            pack_id: "customera".into(),
+            version: semver::Version::new(0, 1, 0),
+            name: "customera".into(),
+            kind: None,
+            description: None,
+            authors: Vec::new(),
+            license: None,
+            imports: Vec::new(),
+            entry_flows: vec![bundle.id.clone()],
+            created_at_utc: "2025-01-01T00:00:00Z".into(),
+            annotations: Default::default(),
+        };
+        let archive = root.path().join("customera.gtpack");
+        PackBuilder::new(meta)
+            .with_flow(bundle)
+            .with_signing(Signing::Dev)
+            .build(&archive)
+            .unwrap();
+
+        for path in [flow, archive] {
+            let flows = pack_flows(&path).unwrap();
+            assert_eq!(flows[0].id, "customera-messaging");
+            assert_eq!(flows[0].nodes["in"].component, "templating.handlebars");
+        }
+    }
+}
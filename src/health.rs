@@ -6,6 +6,7 @@ use tokio::task::JoinHandle;
 use tokio::time;
 
 use parking_lot::Mutex;
+use serde::Serialize;
 
 #[derive(Clone)]
 pub struct HealthMonitor {
@@ -20,6 +21,17 @@ struct TenantHealth {
     ingress_count: u64,
     egress_count: u64,
     error_count: u64,
+    pack_digest: Option<String>,
+}
+
+/// Point-in-time view of one tenant, served as the `/status` payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TenantStatus {
+    pub tenant: String,
+    pub pack_digest: Option<String>,
+    pub ingress: u64,
+    pub egress: u64,
+    pub errors: u64,
 }
 
 pub struct HealthHandle {
@@ -52,6 +64,7 @@ impl HealthMonitor {
                         ingress = health.ingress_count,
                         egress = health.egress_count,
                         errors = health.error_count,
+                        pack_digest = health.pack_digest.as_deref().unwrap_or("unknown"),
                         last_ingress = ?health.last_ingress.map(|_| "recent"),
                         last_egress = ?health.last_egress.map(|_| "recent"),
                         "health summary"
@@ -76,6 +89,30 @@ impl HealthMonitor {
         entry.last_egress = Some(Instant::now());
     }
 
+    /// Records the pack digest `tenant` is running, as resolved by the pack cache.
+    pub fn record_pack(&self, tenant: &str, digest: &str) {
+        let mut guard = self.state.lock();
+        let entry = guard.entry(tenant.to_string()).or_default();
+        entry.pack_digest = Some(digest.to_string());
+    }
+
+    /// Per-tenant snapshot sorted by tenant name.
+    pub fn status(&self) -> Vec<TenantStatus> {
+        let guard = self.state.lock();
+        let mut status: Vec<TenantStatus> = guard
+            .iter()
+            .map(|(tenant, health)| TenantStatus {
+                tenant: tenant.clone(),
+                pack_digest: health.pack_digest.clone(),
+                ingress: health.ingress_count,
+                egress: health.egress_count,
+                errors: health.error_count,
+            })
+            .collect();
+        status.sort_by(|a, b| a.tenant.cmp(&b.tenant));
+        status
+    }
+
     pub fn record_failure(&self, tenant: &str) {
         let mut guard = self.state.lock();
         let entry = guard.entry(tenant.to_string()).or_default();
@@ -0,0 +1,324 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_yaml_bw as serde_yaml;
+use serde_yaml_bw::Value;
+use sha2::Sha256;
+
+use crate::secrets;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Per-tenant rules for client-supplied `flowId`/`nodeId` overrides, read from the
+/// optional `routing_hints` section of `bindings.yaml`.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum HintPolicyConfig {
+    #[default]
+    Disabled,
+    AllowList {
+        #[serde(default)]
+        flows: Vec<String>,
+        #[serde(default)]
+        nodes: Vec<String>,
+    },
+    Signed {
+        secret: String,
+        #[serde(default = "default_max_age_secs")]
+        max_age_secs: u64,
+    },
+}
+
+fn default_max_age_secs() -> u64 {
+    300
+}
+
+#[derive(Debug, Deserialize)]
+struct BindingsHints {
+    #[serde(default)]
+    routing_hints: Option<HintPolicyConfig>,
+}
+
+/// Resolved policy attached to a registered tenant.
+#[derive(Clone)]
+pub enum HintPolicy {
+    Disabled,
+    AllowList {
+        flows: Vec<String>,
+        nodes: Vec<String>,
+    },
+    Signed {
+        key: Vec<u8>,
+        max_age: Duration,
+    },
+}
+
+impl std::fmt::Debug for HintPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HintPolicy::Disabled => f.write_str("Disabled"),
+            HintPolicy::AllowList { flows, nodes } => f
+                .debug_struct("AllowList")
+                .field("flows", flows)
+                .field("nodes", nodes)
+                .finish(),
+            HintPolicy::Signed { max_age, .. } => f
+                .debug_struct("Signed")
+                .field("max_age", max_age)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Routing hints extracted from an activity, with the signature and the fields it covers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutingHints {
+    pub flow: Option<String>,
+    pub node: Option<String>,
+    pub signature: Option<String>,
+    pub activity_id: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl RoutingHints {
+    pub fn is_empty(&self) -> bool {
+        self.flow.is_none() && self.node.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum HintRejection {
+    #[error("routing hints are disabled for this tenant; configure routing_hints in bindings.yaml")]
+    Disabled,
+    #[error("flow `{0}` is not in the routing hint allow-list")]
+    FlowNotAllowed(String),
+    #[error("node `{0}` is not in the routing hint allow-list")]
+    NodeNotAllowed(String),
+    #[error("routing hint signature missing")]
+    MissingSignature,
+    #[error("routing hint signature invalid")]
+    InvalidSignature,
+    #[error("signed routing hints need the activity id and timestamp")]
+    Unbound,
+    #[error("routing hint timestamp is outside the accepted window")]
+    Expired,
+}
+
+impl HintPolicy {
+    /// Reads the `routing_hints` section of resolved bindings (see [`crate::bindings::resolve`]),
+    /// resolving signing keys.
+    pub fn from_bindings(bindings: &Value, tenant: &str) -> Result<Self> {
+        let parsed: BindingsHints =
+            serde_yaml::from_value(bindings.clone()).context("failed to parse routing_hints")?;
+        Self::resolve(parsed.routing_hints.unwrap_or_default(), tenant)
+    }
+
+    pub fn resolve(config: HintPolicyConfig, tenant: &str) -> Result<Self> {
+        Ok(match config {
+            HintPolicyConfig::Disabled => HintPolicy::Disabled,
+            HintPolicyConfig::AllowList { flows, nodes } => HintPolicy::AllowList { flows, nodes },
+            HintPolicyConfig::Signed {
+                secret,
+                max_age_secs,
+            } => {
+                let key = secrets::read_for_tenant_as("hint_policy", tenant, &secret)
+                    .with_context(|| format!("routing hint signing key {secret} unavailable"))?;
+                if key.trim().is_empty() {
+                    return Err(anyhow!("routing hint signing key {secret} is empty"));
+                }
+                HintPolicy::Signed {
+                    key: key.into_bytes(),
+                    max_age: Duration::seconds(i64::try_from(max_age_secs).unwrap_or(i64::MAX)),
+                }
+            }
+        })
+    }
+
+    pub fn mode(&self) -> &'static str {
+        match self {
+            HintPolicy::Disabled => "disabled",
+            HintPolicy::AllowList { .. } => "allow_list",
+            HintPolicy::Signed { .. } => "signed",
+        }
+    }
+
+    /// Checks the hints against the policy. Activities without hints always pass.
+    pub fn check(&self, tenant: &str, hints: &RoutingHints) -> Result<(), HintRejection> {
+        if hints.is_empty() {
+            return Ok(());
+        }
+
+        match self {
+            HintPolicy::Disabled => Err(HintRejection::Disabled),
+            HintPolicy::AllowList { flows, nodes } => {
+                if let Some(flow) = &hints.flow
+                    && !flows.iter().any(|allowed| allowed == flow)
+                {
+                    return Err(HintRejection::FlowNotAllowed(flow.clone()));
+                }
+                if let Some(node) = &hints.node
+                    && !nodes.iter().any(|allowed| allowed == node)
+                {
+                    return Err(HintRejection::NodeNotAllowed(node.clone()));
+                }
+                Ok(())
+            }
+            HintPolicy::Signed { key, max_age } => {
+                let signature = hints
+                    .signature
+                    .as_deref()
+                    .ok_or(HintRejection::MissingSignature)?;
+                let (Some(_), Some(timestamp)) = (&hints.activity_id, hints.timestamp) else {
+                    return Err(HintRejection::Unbound);
+                };
+                if (Utc::now() - timestamp).abs() > *max_age {
+                    return Err(HintRejection::Expired);
+                }
+                let expected =
+                    hex::decode(signature.trim()).map_err(|_| HintRejection::InvalidSignature)?;
+                let mut mac =
+                    HmacSha256::new_from_slice(key).map_err(|_| HintRejection::InvalidSignature)?;
+                mac.update(signing_payload(tenant, hints).as_bytes());
+                mac.verify_slice(&expected)
+                    .map_err(|_| HintRejection::InvalidSignature)
+            }
+        }
+    }
+}
+
+/// Canonical payload covered by a hint signature:
+/// `tenant\nflow\nnode\nactivity_id\nunix_seconds`, empty for absent values.
+pub fn signing_payload(tenant: &str, hints: &RoutingHints) -> String {
+    format!(
+        "{tenant}\n{}\n{}\n{}\n{}",
+        hints.flow.as_deref().unwrap_or_default(),
+        hints.node.as_deref().unwrap_or_default(),
+        hints.activity_id.as_deref().unwrap_or_default(),
+        hints
+            .timestamp
+            .map(|timestamp| timestamp.timestamp().to_string())
+            .unwrap_or_default()
+    )
+}
+
+/// Produces the hex-encoded HMAC-SHA256 signature a trusted client attaches as `hintSignature`.
+pub fn sign(key: &[u8], tenant: &str, hints: &RoutingHints) -> Result<String> {
+    let mut mac =
+        HmacSha256::new_from_slice(key).map_err(|err| anyhow!("invalid hint key: {err}"))?;
+    mac.update(signing_payload(tenant, hints).as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret_policy::SecretPolicy;
+
+    fn hints(flow: Option<&str>, node: Option<&str>) -> RoutingHints {
+        RoutingHints {
+            flow: flow.map(str::to_string),
+            node: node.map(str::to_string),
+            ..RoutingHints::default()
+        }
+    }
+
+    #[test]
+    fn disabled_rejects_any_hint() {
+        let policy = HintPolicy::Disabled;
+        assert!(policy.check("customera", &hints(None, None)).is_ok());
+        assert_eq!(
+            policy.check("customera", &hints(Some("weather_bot"), None)),
+            Err(HintRejection::Disabled)
+        );
+    }
+
+    #[test]
+    fn allow_list_checks_flows_and_nodes() {
+        let policy = HintPolicy::AllowList {
+            flows: vec!["weather_bot".into()],
+            nodes: vec!["qa_node".into()],
+        };
+        assert!(
+            policy
+                .check("customera", &hints(Some("weather_bot"), Some("qa_node")))
+                .is_ok()
+        );
+        assert_eq!(
+            policy.check("customera", &hints(Some("admin"), None)),
+            Err(HintRejection::FlowNotAllowed("admin".into()))
+        );
+        assert_eq!(
+            policy.check("customera", &hints(None, Some("debug"))),
+            Err(HintRejection::NodeNotAllowed("debug".into()))
+        );
+    }
+
+    #[test]
+    fn signed_hints_are_bound_to_tenant_activity_and_time() {
+        let key = b"hint-key".to_vec();
+        let policy = HintPolicy::Signed {
+            key: key.clone(),
+            max_age: Duration::minutes(5),
+        };
+        let mut signed = hints(Some("weather_bot"), None);
+        assert_eq!(
+            policy.check("customera", &signed),
+            Err(HintRejection::MissingSignature)
+        );
+        signed.signature = Some(sign(&key, "customera", &signed).unwrap());
+        assert_eq!(
+            policy.check("customera", &signed),
+            Err(HintRejection::Unbound)
+        );
+
+        signed.activity_id = Some("act-1".into());
+        signed.timestamp = Some(Utc::now());
+        signed.signature = Some(sign(&key, "customera", &signed).unwrap());
+        assert!(policy.check("customera", &signed).is_ok());
+        assert_eq!(
+            policy.check("customerb", &signed),
+            Err(HintRejection::InvalidSignature)
+        );
+
+        let replayed = RoutingHints {
+            activity_id: Some("act-2".into()),
+            ..signed.clone()
+        };
+        assert_eq!(
+            policy.check("customera", &replayed),
+            Err(HintRejection::InvalidSignature)
+        );
+
+        let mut stale = signed;
+        stale.timestamp = Some(Utc::now() - Duration::hours(1));
+        stale.signature = Some(sign(&key, "customera", &stale).unwrap());
+        assert_eq!(
+            policy.check("customera", &stale),
+            Err(HintRejection::Expired)
+        );
+    }
+
+    #[test]
+    fn reads_policy_from_resolved_bindings() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("base.yaml"),
+            "routing_hints:\n  mode: allow_list\n  flows: [weather_bot]\n",
+        )
+        .unwrap();
+        let path = dir.path().join("bindings.yaml");
+        std::fs::write(&path, "extends: base.yaml\ntenant: customera\n").unwrap();
+
+        let resolved =
+            crate::bindings::resolve(&path, "customera", &SecretPolicy::default()).unwrap();
+        let policy = HintPolicy::from_bindings(&resolved, "customera").unwrap();
+
+        assert_eq!(policy.mode(), "allow_list");
+        assert!(
+            policy
+                .check("customera", &hints(Some("weather_bot"), None))
+                .is_ok()
+        );
+    }
+}
@@ -1,23 +1,33 @@
-#[cfg(feature = "runner-shim")]
+pub mod bindings;
+pub mod chat;
 pub mod config;
-#[cfg(feature = "runner-shim")]
+pub mod config_check;
+pub mod config_file;
+pub mod duration;
+pub mod flow_engine;
+pub mod flow_mocks;
+pub mod flow_observer;
+pub mod gtpack;
 pub mod health;
+pub mod hint_policy;
 pub mod loader;
-#[cfg(feature = "runner-shim")]
 pub mod logging;
-#[cfg(feature = "runner-shim")]
 pub mod nats_bridge;
-#[cfg(feature = "runner-shim")]
+pub mod pack_cache;
+pub mod pack_signing;
 pub mod runner_bridge;
 #[cfg(any(feature = "runner-shim", feature = "use-runner-api"))]
 pub mod runner_shim;
-#[cfg(feature = "runner-shim")]
+pub mod secret_audit;
+pub mod secret_bundle;
+pub mod secret_policy;
+pub mod secret_rotation;
 pub mod secrets;
-#[cfg(feature = "runner-shim")]
 pub mod telemetry;
-#[cfg(feature = "runner-shim")]
 pub mod types;
+pub mod validate;
 
-#[cfg(feature = "runner-shim")]
 pub use config::{AppConfig, CliArgs, Mode, SubjectConfig};
-pub use loader::{TenantPack, load_packs};
+pub use loader::{
+    LoadProblem, LoadReport, TenantPack, discover_tenants, load_index, load_packs, record_served,
+};
@@ -1,75 +1,670 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_yaml_bw as serde_yaml;
+use thiserror::Error;
+use url::Url;
+
+use crate::bindings::{self, RESOLVED_DIR};
+use crate::gtpack::{Gtpack, GtpackManifest, is_gtpack};
+use crate::pack_cache::{OpenedPack, PackCache};
+use crate::pack_signing::{PackVerifier, SIGNATURE_EXTENSION};
 
 #[derive(Debug, Clone)]
 pub struct TenantPack {
     pub tenant: String,
     pub index_path: PathBuf,
+    /// Bindings the runner loads: `bindings_source` itself, or its rendering in the pack
+    /// cache when it uses `extends:` or placeholders (see [`bindings::render`]).
     pub bindings_path: PathBuf,
+    /// Bindings file as shipped with the pack.
+    pub bindings_source: PathBuf,
+    /// Packs layered on top of `index_path`, in declared order; later packs win.
+    pub overlays: Vec<PathBuf>,
+    /// Manifest of the main pack when it is a source-layout `.gtpack`.
+    pub manifest: Option<GtpackManifest>,
+    /// Content digest of the main pack (`sha256:<hex>`), see [`PackCache::open_pack`].
+    pub digest: String,
+    /// Content digests of `overlays`, in the same order.
+    pub overlay_digests: Vec<String>,
 }
 
-pub fn load_packs(packs_dir: &Path) -> Result<Vec<TenantPack>> {
-    let mut packs = Vec::new();
-    let entries = fs::read_dir(packs_dir)
-        .with_context(|| format!("packs directory {packs_dir:?} does not exist"))?;
+/// Tenants that loaded plus everything that kept other tenants from loading.
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    pub tenants: Vec<TenantPack>,
+    pub problems: Vec<LoadProblem>,
+}
 
-    for entry in entries {
-        let entry = match entry {
-            Ok(value) => value,
-            Err(err) => {
-                tracing::warn!(error = %err, "skipping unreadable directory entry");
+/// Why a tenant was not loaded, or loaded with a configuration inconsistency.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LoadProblem {
+    #[error("cannot read {dir}: {reason}")]
+    Unreadable { dir: PathBuf, reason: String },
+    #[error("{dir} is not a valid tenant name (directory name is not UTF-8)")]
+    InvalidTenantName { dir: PathBuf },
+    #[error("tenant {tenant}: no index.ygtc or .gtpack in {dir}")]
+    MissingIndex { tenant: String, dir: PathBuf },
+    #[error("tenant {tenant}: bindings.yaml not found in {dir}")]
+    MissingBindings { tenant: String, dir: PathBuf },
+    #[error("tenant {tenant}: invalid bindings {bindings}: {reason}")]
+    InvalidBindings {
+        tenant: String,
+        bindings: PathBuf,
+        reason: String,
+    },
+    #[error("tenant {tenant}: invalid pack: {reason}")]
+    InvalidPack { tenant: String, reason: String },
+    #[error("tenant {tenant}: {reason}")]
+    VerificationFailed { tenant: String, reason: String },
+    #[error("tenant {tenant}: {reason}")]
+    InvalidTenantId { tenant: String, reason: String },
+    #[error("tenant {tenant}: {bindings} declares tenant `{declared}`")]
+    TenantMismatch {
+        tenant: String,
+        declared: String,
+        bindings: PathBuf,
+    },
+    #[error(
+        "tenant {tenant}: collides with the tenant loaded from {first} (ids are compared ignoring case)"
+    )]
+    DuplicateTenant {
+        tenant: String,
+        first: PathBuf,
+        bindings: PathBuf,
+    },
+}
+
+impl LoadReport {
+    pub fn is_clean(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Logs every problem and returns the loaded tenants; with `strict`, any problem is an error.
+    pub fn into_tenants(self, strict: bool) -> Result<Vec<TenantPack>> {
+        for problem in &self.problems {
+            tracing::error!(problem = %problem, "tenant pack problem");
+        }
+        if strict && !self.is_clean() {
+            let problems = self
+                .problems
+                .iter()
+                .map(|problem| format!("  - {problem}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            bail!(
+                "strict pack loading found {} problem(s):\n{problems}",
+                self.problems.len()
+            );
+        }
+        Ok(self.tenants)
+    }
+
+    /// Refuses tenants whose id disagrees with their bindings' `tenant:` field, and later
+    /// tenants whose id collides with an earlier one ignoring case.
+    fn check_tenant_ids(&mut self) {
+        let mut claimed: BTreeMap<String, PathBuf> = BTreeMap::new();
+        let mut kept = Vec::with_capacity(self.tenants.len());
+        for pack in std::mem::take(&mut self.tenants) {
+            let declared = match declared_tenant(&pack.bindings_path) {
+                Ok(Some(declared)) => declared,
+                Ok(None) => pack.tenant.clone(),
+                Err(err) => {
+                    self.problems.push(LoadProblem::InvalidBindings {
+                        tenant: pack.tenant.clone(),
+                        bindings: pack.bindings_source.clone(),
+                        reason: format!("{err:#}"),
+                    });
+                    continue;
+                }
+            };
+            if declared != pack.tenant {
+                self.problems.push(LoadProblem::TenantMismatch {
+                    tenant: pack.tenant.clone(),
+                    declared,
+                    bindings: pack.bindings_source.clone(),
+                });
                 continue;
             }
+            let key = pack.tenant.to_ascii_lowercase();
+            if let Some(first) = claimed.get(&key) {
+                self.problems.push(LoadProblem::DuplicateTenant {
+                    tenant: pack.tenant.clone(),
+                    first: first.clone(),
+                    bindings: pack.bindings_source.clone(),
+                });
+                continue;
+            }
+            claimed.insert(key, pack.bindings_source.clone());
+            kept.push(pack);
+        }
+        self.tenants = kept;
+    }
+}
+
+/// Longest tenant id accepted; ids end up in subjects, cache paths and env-style keys.
+pub const MAX_TENANT_ID_LEN: usize = 64;
+
+/// Tenant ids must be a single NATS subject token and path component: ASCII letters,
+/// digits, `-` and `_`.
+pub fn validate_tenant_id(id: &str) -> Result<(), String> {
+    if id.is_empty() {
+        return Err("tenant id is empty".into());
+    }
+    if id.len() > MAX_TENANT_ID_LEN {
+        return Err(format!(
+            "tenant id is longer than {MAX_TENANT_ID_LEN} characters"
+        ));
+    }
+    if let Some(ch) = id
+        .chars()
+        .find(|ch| !(ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_')))
+    {
+        return Err(format!(
+            "tenant id contains {ch:?}; only ASCII letters, digits, '-' and '_' are allowed"
+        ));
+    }
+    Ok(())
+}
+
+/// [`validate_tenant_id`] as a load problem; runs before the id touches the filesystem.
+fn check_tenant_id(tenant: &str) -> Result<(), LoadProblem> {
+    validate_tenant_id(tenant).map_err(|reason| LoadProblem::InvalidTenantId {
+        tenant: tenant.to_string(),
+        reason,
+    })
+}
+
+#[derive(Deserialize)]
+struct BindingsTenant {
+    #[serde(default)]
+    tenant: Option<String>,
+}
+
+/// The `tenant:` field of a bindings file, if present.
+fn declared_tenant(bindings_path: &Path) -> Result<Option<String>> {
+    let contents = fs::read_to_string(bindings_path)
+        .with_context(|| format!("failed to read {}", bindings_path.display()))?;
+    let bindings: BindingsTenant = serde_yaml::from_str(&contents)
+        .with_context(|| format!("failed to parse {}", bindings_path.display()))?;
+    Ok(bindings.tenant)
+}
+
+/// Tenant → pack mapping in the runner's `index.json` format.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PackIndex {
+    #[serde(flatten)]
+    pub tenants: BTreeMap<String, IndexEntry>,
+    #[serde(skip)]
+    base: IndexBase,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexEntry {
+    pub main_pack: PackRef,
+    #[serde(default)]
+    pub overlays: Vec<PackRef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PackRef {
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    pub locator: String,
+}
+
+/// Where relative locators in an index are anchored.
+#[derive(Debug, Clone, Default)]
+enum IndexBase {
+    #[default]
+    Cwd,
+    Dir(PathBuf),
+    Url(Url),
+}
+
+/// A pack locator after resolution against its index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackLocation {
+    Local(PathBuf),
+    Remote(Url),
+}
+
+impl PackIndex {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read pack index {path:?}"))?;
+        let mut index: PackIndex = serde_json::from_str(&contents)
+            .with_context(|| format!("pack index {path:?} is not valid JSON"))?;
+        index.base = IndexBase::Dir(
+            path.parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        );
+        Ok(index)
+    }
+
+    /// Loads an index from a local path or an `http(s)://` URL (e.g. a local pack server).
+    pub async fn fetch(location: &str) -> Result<Self> {
+        let Some(url) = remote_url(location)? else {
+            return Self::load(Path::new(
+                location.strip_prefix("fs://").unwrap_or(location),
+            ));
         };
+        let body = http_get(&url).await?;
+        let mut index: PackIndex = serde_json::from_slice(&body)
+            .with_context(|| format!("pack index {url} is not valid JSON"))?;
+        index.base = IndexBase::Url(url);
+        Ok(index)
+    }
 
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
+    /// Resolves a locator: relative paths (with or without `fs://`) are anchored at the
+    /// index file, or joined onto the index URL when the index itself was fetched remotely.
+    pub fn resolve_locator(&self, pack: &PackRef) -> Result<PackLocation> {
+        if let Some(url) = remote_url(&pack.locator)? {
+            return Ok(PackLocation::Remote(url));
+        }
+        let raw = match pack.locator.split_once("://") {
+            Some(("fs", rest)) => rest,
+            Some((scheme, _)) => bail!(
+                "pack {} uses unsupported locator scheme `{scheme}://`",
+                pack.name
+            ),
+            None => pack.locator.as_str(),
+        };
+        let path = PathBuf::from(raw);
+        if path.is_absolute() {
+            return Ok(PackLocation::Local(path));
         }
+        Ok(match &self.base {
+            IndexBase::Cwd => PackLocation::Local(path),
+            IndexBase::Dir(dir) => PackLocation::Local(dir.join(path)),
+            IndexBase::Url(base) => PackLocation::Remote(
+                base.join(raw)
+                    .with_context(|| format!("invalid locator {} for {base}", pack.locator))?,
+            ),
+        })
+    }
 
-        let tenant_name = match path.file_name().and_then(|n| n.to_str()) {
-            Some(name) => name.to_string(),
-            None => {
-                tracing::warn!(path = ?path, "unable to derive tenant name; skipping");
-                continue;
+    /// Builds one [`TenantPack`] per index entry, downloading remote packs into `cache`.
+    pub async fn tenant_packs(&self, cache: &PackCache, verifier: &PackVerifier) -> LoadReport {
+        let mut report = LoadReport::default();
+        for (tenant, entry) in &self.tenants {
+            match self.resolve_entry(tenant, entry, cache, verifier).await {
+                Ok(pack) => report.tenants.push(pack),
+                Err(problem) => report.problems.push(problem),
             }
+        }
+        report.check_tenant_ids();
+        tracing::info!(
+            count = report.tenants.len(),
+            problems = report.problems.len(),
+            "packs loaded from index"
+        );
+        report
+    }
+
+    async fn resolve_entry(
+        &self,
+        tenant: &str,
+        entry: &IndexEntry,
+        cache: &PackCache,
+        verifier: &PackVerifier,
+    ) -> Result<TenantPack, LoadProblem> {
+        check_tenant_id(tenant)?;
+        let invalid = |err: anyhow::Error| LoadProblem::InvalidPack {
+            tenant: tenant.to_string(),
+            reason: format!("{err:#}"),
         };
 
-        let index_path = path.join("index.ygtc");
-        if !index_path.exists() {
-            tracing::warn!(tenant = %tenant_name, path = ?index_path, "tenant pack missing index.ygtc");
-            continue;
+        let main = self.resolve_locator(&entry.main_pack).map_err(invalid)?;
+        let pack_dir = match &main {
+            PackLocation::Local(path) => path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+            PackLocation::Remote(_) => cache.root().join("bindings").join(tenant),
+        };
+        let fetched = materialize(&main, cache, Some(&pack_dir))
+            .await
+            .context("failed to fetch main pack")
+            .map_err(invalid)?;
+        let mut signed = Vec::with_capacity(1 + entry.overlays.len());
+        let opened = open_pack(cache, fetched, &mut signed).map_err(invalid)?;
+
+        let bindings_source = discover_bindings(&pack_dir)
+            .or_else(|| opened.gtpack.as_ref().and_then(gtpack_bindings))
+            .ok_or_else(|| LoadProblem::MissingBindings {
+                tenant: tenant.to_string(),
+                dir: pack_dir.clone(),
+            })?;
+        let bindings_path = render_bindings(tenant, &bindings_source, cache)?;
+
+        let mut overlays = Vec::with_capacity(entry.overlays.len());
+        let mut overlay_digests = Vec::with_capacity(entry.overlays.len());
+        for overlay in &entry.overlays {
+            let location = self.resolve_locator(overlay).map_err(invalid)?;
+            let path = materialize(&location, cache, None)
+                .await
+                .with_context(|| format!("failed to fetch overlay {}", overlay.name))
+                .map_err(invalid)?;
+            let layer = open_pack(cache, path, &mut signed)
+                .with_context(|| format!("invalid overlay {}", overlay.name))
+                .map_err(invalid)?;
+            overlays.push(layer.entry);
+            overlay_digests.push(layer.digest);
         }
 
-        let bindings_path = match discover_bindings(&path) {
-            Some(path) => path,
-            None => {
-                tracing::error!(
-                    tenant = %tenant_name,
-                    dir = %path.display(),
-                    "bindings.yaml not found; please add one per tenant"
-                );
+        verifier
+            .verify_tenant(tenant, &bindings_path, &signed)
+            .map_err(|err| LoadProblem::VerificationFailed {
+                tenant: tenant.to_string(),
+                reason: format!("{err:#}"),
+            })?;
+
+        tracing::info!(tenant = %tenant, digest = %opened.digest, "tenant pack resolved");
+        Ok(TenantPack {
+            tenant: tenant.to_string(),
+            index_path: opened.entry,
+            bindings_path,
+            bindings_source,
+            overlays,
+            manifest: opened.gtpack.and_then(|pack| pack.manifest().cloned()),
+            digest: opened.digest,
+            overlay_digests,
+        })
+    }
+}
+
+/// Loads tenants from a pack index (`PACK_INDEX_URL`) instead of scanning a directory.
+pub async fn load_index(
+    location: &str,
+    cache_dir: &Path,
+    verifier: &PackVerifier,
+) -> Result<LoadReport> {
+    let cache = PackCache::open(cache_dir)?;
+    let index = PackIndex::fetch(location).await?;
+    Ok(index.tenant_packs(&cache, verifier).await)
+}
+
+/// Records the tenants a serving process runs in the pack cache and evicts objects
+/// neither this nor the previous record references. Read-only commands never call it.
+pub fn record_served(cache_dir: &Path, packs: &[TenantPack]) -> Result<()> {
+    PackCache::open(cache_dir)?.record(packs)
+}
+
+/// Resolves tenants from `index` when one is configured, otherwise by scanning `packs_dir`.
+pub async fn discover_tenants(
+    packs_dir: &Path,
+    index: Option<&str>,
+    cache_dir: &Path,
+    verifier: &PackVerifier,
+) -> Result<LoadReport> {
+    match index {
+        Some(location) => load_index(location, cache_dir, verifier)
+            .await
+            .with_context(|| format!("failed to load tenant packs from index {location}")),
+        None => load_packs(packs_dir, cache_dir, verifier),
+    }
+}
+
+fn remote_url(locator: &str) -> Result<Option<Url>> {
+    if !(locator.starts_with("http://") || locator.starts_with("https://")) {
+        return Ok(None);
+    }
+    Url::parse(locator)
+        .map(Some)
+        .with_context(|| format!("invalid pack URL {locator}"))
+}
+
+async fn http_get(url: &Url) -> Result<Vec<u8>> {
+    let response = reqwest::get(url.clone())
+        .await
+        .with_context(|| format!("failed to fetch {url}"))?
+        .error_for_status()
+        .with_context(|| format!("download failed for {url}"))?;
+    let bytes = response
+        .bytes()
+        .await
+        .with_context(|| format!("failed to read body of {url}"))?;
+    Ok(bytes.to_vec())
+}
+
+/// Returns a local path for `location`, downloading remote packs (and their `.sig`) into
+/// `cache`, or the last cached download when the server is unreachable.
+async fn materialize(
+    location: &PackLocation,
+    cache: &PackCache,
+    bindings_dir: Option<&Path>,
+) -> Result<PathBuf> {
+    let url = match location {
+        PackLocation::Local(path) => {
+            if !path.exists() {
+                bail!("pack not found at {}", path.display());
+            }
+            return Ok(path.clone());
+        }
+        PackLocation::Remote(url) => url,
+    };
+
+    let file_name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("index.ygtc");
+    let body = match http_get(url).await {
+        Ok(body) => body,
+        Err(err) => match cache.cached_download(url) {
+            Some(path) => {
+                tracing::warn!(url = %url, path = %path.display(), error = %format!("{err:#}"), "pack server unavailable; using cached pack");
+                return Ok(path);
+            }
+            None => return Err(err),
+        },
+    };
+    let path = cache.store_download(url, file_name, &body)?;
+
+    let sig_name = format!("{file_name}.{SIGNATURE_EXTENSION}");
+    if let Ok(sig_url) = url.join(&sig_name) {
+        match http_get(&sig_url).await {
+            Ok(body) => {
+                let target = path.with_file_name(&sig_name);
+                fs::write(&target, body)
+                    .with_context(|| format!("failed to write {}", target.display()))?;
+            }
+            Err(err) => {
+                tracing::debug!(url = %sig_url, error = %err, "pack signature unavailable");
+            }
+        }
+    }
+
+    if let Some(dir) = bindings_dir {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        for candidate in BINDINGS_CANDIDATES {
+            let Ok(bindings_url) = url.join(candidate) else {
                 continue;
+            };
+            match http_get(&bindings_url).await {
+                Ok(body) => {
+                    let target = dir.join(candidate);
+                    fs::write(&target, body)
+                        .with_context(|| format!("failed to write {}", target.display()))?;
+                    break;
+                }
+                Err(err) => {
+                    tracing::debug!(url = %bindings_url, error = %err, "bindings candidate unavailable");
+                }
             }
-        };
+        }
+    }
 
-        packs.push(TenantPack {
-            tenant: tenant_name,
-            index_path,
-            bindings_path,
-        });
+    tracing::debug!(url = %url, path = %path.display(), "pack downloaded");
+    Ok(path)
+}
+
+/// Scans `packs_dir/<tenant>/` for `index.ygtc` or a `.gtpack` plus `bindings.yaml`,
+/// skipping `_` and `.` directories.
+pub fn load_packs(
+    packs_dir: &Path,
+    cache_dir: &Path,
+    verifier: &PackVerifier,
+) -> Result<LoadReport> {
+    let cache = PackCache::open(cache_dir)?;
+    let mut report = LoadReport::default();
+    let entries = fs::read_dir(packs_dir)
+        .with_context(|| format!("packs directory {packs_dir:?} does not exist"))?;
+
+    let mut dirs = Vec::new();
+    for entry in entries {
+        match entry {
+            Ok(entry) if entry.path().is_dir() && !is_shared_dir(&entry.path()) => {
+                dirs.push(entry.path())
+            }
+            Ok(_) => {}
+            Err(err) => report.problems.push(LoadProblem::Unreadable {
+                dir: packs_dir.to_path_buf(),
+                reason: err.to_string(),
+            }),
+        }
     }
+    dirs.sort();
 
-    tracing::info!(count = packs.len(), base = ?packs_dir, "packs discovered");
-    Ok(packs)
+    for dir in dirs {
+        match scan_tenant(&dir, &cache, verifier) {
+            Ok(pack) => report.tenants.push(pack),
+            Err(problem) => report.problems.push(problem),
+        }
+    }
+    report.check_tenant_ids();
+
+    tracing::info!(
+        count = report.tenants.len(),
+        problems = report.problems.len(),
+        base = ?packs_dir,
+        "packs discovered"
+    );
+    Ok(report)
 }
 
-fn discover_bindings(pack_dir: &Path) -> Option<PathBuf> {
-    const CANDIDATES: &[&str] = &["bindings.yaml", "bindings.yml"];
+fn scan_tenant(
+    dir: &Path,
+    cache: &PackCache,
+    verifier: &PackVerifier,
+) -> Result<TenantPack, LoadProblem> {
+    let tenant = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| LoadProblem::InvalidTenantName {
+            dir: dir.to_path_buf(),
+        })?
+        .to_string();
+    check_tenant_id(&tenant)?;
+
+    let mut candidate = dir.join("index.ygtc");
+    if !candidate.exists() {
+        candidate = find_gtpack(dir).ok_or_else(|| LoadProblem::MissingIndex {
+            tenant: tenant.clone(),
+            dir: dir.to_path_buf(),
+        })?;
+    }
+    let mut signed = Vec::with_capacity(1);
+    let opened =
+        open_pack(cache, candidate, &mut signed).map_err(|err| LoadProblem::InvalidPack {
+            tenant: tenant.clone(),
+            reason: format!("{err:#}"),
+        })?;
+
+    let bindings_source = discover_bindings(dir)
+        .or_else(|| opened.gtpack.as_ref().and_then(gtpack_bindings))
+        .ok_or_else(|| LoadProblem::MissingBindings {
+            tenant: tenant.clone(),
+            dir: dir.to_path_buf(),
+        })?;
+    let bindings_path = render_bindings(&tenant, &bindings_source, cache)?;
+
+    verifier
+        .verify_tenant(&tenant, &bindings_path, &signed)
+        .map_err(|err| LoadProblem::VerificationFailed {
+            tenant: tenant.clone(),
+            reason: format!("{err:#}"),
+        })?;
+
+    tracing::info!(tenant = %tenant, digest = %opened.digest, "tenant pack resolved");
+    Ok(TenantPack {
+        tenant,
+        index_path: opened.entry,
+        bindings_path,
+        bindings_source,
+        overlays: Vec::new(),
+        manifest: opened.gtpack.and_then(|pack| pack.manifest().cloned()),
+        digest: opened.digest,
+        overlay_digests: Vec::new(),
+    })
+}
 
-    for candidate in CANDIDATES {
+fn is_shared_dir(dir: &Path) -> bool {
+    dir.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('_') || name.starts_with('.'))
+}
+
+/// Renders `source` into the pack cache when it uses `extends:` or placeholders.
+fn render_bindings(tenant: &str, source: &Path, cache: &PackCache) -> Result<PathBuf, LoadProblem> {
+    bindings::render(source, tenant, &cache.root().join(RESOLVED_DIR)).map_err(|err| {
+        LoadProblem::InvalidBindings {
+            tenant: tenant.to_string(),
+            bindings: source.to_path_buf(),
+            reason: format!("{err:#}"),
+        }
+    })
+}
+
+/// Resolves `path` through the pack cache and pushes its `(source, content)` onto `signed`.
+fn open_pack(
+    cache: &PackCache,
+    path: PathBuf,
+    signed: &mut Vec<(PathBuf, PathBuf)>,
+) -> Result<OpenedPack> {
+    let opened = cache.open_pack(&path)?;
+    let content = match &opened.gtpack {
+        Some(pack) => pack
+            .root()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| pack.entry_path()),
+        None => path.clone(),
+    };
+    let is_manifest = path.file_name().and_then(|n| n.to_str()) == Some("manifest.yaml");
+    let source = match path.parent() {
+        Some(parent) if is_manifest => parent.to_path_buf(),
+        _ => path,
+    };
+    signed.push((source, content));
+    Ok(opened)
+}
+
+/// First `*.gtpack` directory or archive inside a tenant directory, by name.
+fn find_gtpack(tenant_dir: &Path) -> Option<PathBuf> {
+    let mut candidates: Vec<PathBuf> = fs::read_dir(tenant_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_gtpack(path))
+        .collect();
+    candidates.sort();
+    candidates.into_iter().next()
+}
+
+fn gtpack_bindings(pack: &Gtpack) -> Option<PathBuf> {
+    pack.root().and_then(discover_bindings)
+}
+
+const BINDINGS_CANDIDATES: &[&str] = &["bindings.yaml", "bindings.yml"];
+
+fn discover_bindings(pack_dir: &Path) -> Option<PathBuf> {
+    for candidate in BINDINGS_CANDIDATES {
         let path = pack_dir.join(candidate);
         if path.exists() {
             return Some(path);
@@ -78,3 +673,160 @@ fn discover_bindings(pack_dir: &Path) -> Option<PathBuf> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn index_tenants_resolve_relative_to_index_with_overlays_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let tenant_dir = root.join("packs").join("customera");
+        let overlays = root.join("overlays");
+        fs::create_dir_all(&tenant_dir).unwrap();
+        fs::create_dir_all(&overlays).unwrap();
+        fs::write(tenant_dir.join("index.ygtc"), "").unwrap();
+        fs::write(tenant_dir.join("bindings.yaml"), "tenant: customera").unwrap();
+        fs::write(overlays.join("brand.ygtc"), "").unwrap();
+        fs::write(overlays.join("faq.ygtc"), "").unwrap();
+        let index_path = root.join("index.json");
+        fs::write(
+            &index_path,
+            r#"{
+              "customera": {
+                "main_pack": { "name": "base", "version": "1.0.0", "locator": "fs://packs/customera/index.ygtc" },
+                "overlays": [
+                  { "name": "brand", "version": "1.0.0", "locator": "fs://overlays/brand.ygtc" },
+                  { "name": "faq", "version": "1.0.0", "locator": "overlays/faq.ygtc" }
+                ]
+              }
+            }"#,
+        )
+        .unwrap();
+
+        let packs = load_index(
+            index_path.to_str().unwrap(),
+            &root.join("cache"),
+            &PackVerifier::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(packs.is_clean());
+        let packs = packs.tenants;
+        assert_eq!(packs.len(), 1);
+        assert_eq!(packs[0].index_path, tenant_dir.join("index.ygtc"));
+        assert_eq!(packs[0].bindings_path, tenant_dir.join("bindings.yaml"));
+        assert_eq!(
+            packs[0].overlays,
+            vec![overlays.join("brand.ygtc"), overlays.join("faq.ygtc")]
+        );
+    }
+
+    #[test]
+    fn scan_reports_missing_files_mismatches_and_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let packs_dir = root.join("packs");
+        for (dir, bindings) in [
+            ("a.b", Some("tenant: a.b")),
+            ("alpha", Some("tenant: alpha")),
+            ("beta", Some("tenant: alpha")),
+            ("gamma", None),
+            ("epsilon", Some("tenant: [epsilon]")),
+        ] {
+            let dir = packs_dir.join(dir);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("index.ygtc"), "").unwrap();
+            if let Some(bindings) = bindings {
+                fs::write(dir.join("bindings.yaml"), bindings).unwrap();
+            }
+        }
+        fs::create_dir_all(packs_dir.join("delta")).unwrap();
+
+        let report = load_packs(&packs_dir, &root.join("cache"), &PackVerifier::default()).unwrap();
+
+        let tenants: Vec<_> = report.tenants.iter().map(|p| p.tenant.as_str()).collect();
+        assert_eq!(tenants, vec!["alpha"]);
+        let kinds: Vec<_> = report
+            .problems
+            .iter()
+            .map(|problem| serde_json::to_value(problem).unwrap()["kind"].clone())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "invalid_tenant_id",
+                "missing_index",
+                "missing_bindings",
+                "tenant_mismatch",
+                "invalid_bindings"
+            ]
+        );
+        assert!(report.clone().into_tenants(true).is_err());
+        assert_eq!(report.clone().into_tenants(false).unwrap().len(), 1);
+
+        // Ids differing only by case collide; the first one loaded wins.
+        let mut shouting = report.tenants[0].clone();
+        shouting.tenant = "ALPHA".into();
+        shouting.bindings_path = root.join("shouting.yaml");
+        fs::write(&shouting.bindings_path, "flow_type_bindings: {}").unwrap();
+        let mut collided = LoadReport {
+            tenants: vec![report.tenants[0].clone(), shouting],
+            problems: Vec::new(),
+        };
+        collided.check_tenant_ids();
+        assert_eq!(collided.tenants.len(), 1);
+        assert!(matches!(
+            collided.problems.as_slice(),
+            [LoadProblem::DuplicateTenant { tenant, .. }] if tenant == "ALPHA"
+        ));
+    }
+
+    #[tokio::test]
+    async fn index_tenant_ids_are_checked_before_touching_the_cache() {
+        let root = tempfile::tempdir().unwrap();
+        let index = PackIndex {
+            tenants: BTreeMap::from([(
+                "../escape".to_string(),
+                IndexEntry {
+                    main_pack: PackRef {
+                        name: "base".into(),
+                        version: None,
+                        locator: "http://127.0.0.1:9/index.ygtc".into(),
+                    },
+                    overlays: Vec::new(),
+                },
+            )]),
+            base: IndexBase::Cwd,
+        };
+        let cache = PackCache::open(&root.path().join("cache")).unwrap();
+        let report = index.tenant_packs(&cache, &PackVerifier::default()).await;
+        assert!(matches!(
+            report.problems.as_slice(),
+            [LoadProblem::InvalidTenantId { tenant, .. }] if tenant == "../escape"
+        ));
+        assert!(!root.path().join("cache").join("escape").exists());
+        assert!(!cache.root().join("bindings").exists());
+    }
+
+    #[test]
+    fn relative_locators_join_remote_index_url() {
+        let index = PackIndex {
+            tenants: BTreeMap::new(),
+            base: IndexBase::Url(Url::parse("http://127.0.0.1:8000/index.json").unwrap()),
+        };
+        let pack = PackRef {
+            name: "base".into(),
+            version: None,
+            locator: "packs/customera/index.ygtc".into(),
+        };
+        assert_eq!(
+            index.resolve_locator(&pack).unwrap(),
+            PackLocation::Remote(
+                Url::parse("http://127.0.0.1:8000/packs/customera/index.ygtc").unwrap()
+            )
+        );
+    }
+}
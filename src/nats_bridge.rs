@@ -1,25 +1,41 @@
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use async_nats::{AuthError, Client, ConnectOptions, Message};
+use async_nats::{Auth, AuthError, Client, ConnectOptions, Message};
 use futures::StreamExt;
+use parking_lot::RwLock;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
 use tokio::task::JoinSet;
 
 use crate::SubjectConfig;
-use crate::config::{AppConfig, Mode, NatsAuth};
+use crate::config::{AppConfig, Mode, NatsAuth, NatsConfig};
 use crate::health::HealthMonitor;
 use crate::runner_bridge::RunnerBridge;
+use crate::secret_audit::{self, AuditSink, SecretAccess};
+use crate::secret_rotation::{RotatedSecrets, SecretWatcher};
 use crate::types::Activity;
 
 pub struct NatsBridge {
     client: Client,
+    credentials: Option<SharedCredentials>,
     runner: RunnerBridge,
     mode: Mode,
     tenants: Vec<String>,
     subjects: SubjectConfig,
     health: HealthMonitor,
+    max_activity_bytes: usize,
 }
 
+/// JWT credentials read by the auth callback on every (re)connect.
+#[derive(Clone)]
+struct NatsCredentials {
+    jwt: String,
+    seed: String,
+}
+
+type SharedCredentials = Arc<RwLock<NatsCredentials>>;
+
 impl NatsBridge {
     pub async fn connect(
         config: &AppConfig,
@@ -27,7 +43,20 @@ impl NatsBridge {
         tenants: Vec<String>,
         health: HealthMonitor,
     ) -> Result<Self> {
-        let client = connect_client(&config.nats).await?;
+        let credentials = shared_credentials(&config.nats.auth);
+        let client = connect_client(&config.nats.url, credentials.clone()).await?;
+        if let Some(subject) = &config.secrets_audit_subject {
+            secret_audit::add_sink(Arc::new(NatsAuditSink::spawn(
+                client.clone(),
+                subject.clone(),
+            )));
+            tracing::info!(subject = %subject, "publishing secret access audit events");
+        }
+        for tenant in &tenants {
+            if let Some(digest) = runner.pack_digest(tenant).await {
+                health.record_pack(tenant, &digest);
+            }
+        }
         tracing::info!(
             url = %config.nats.url,
             mode = ?config.mode,
@@ -37,14 +66,58 @@ impl NatsBridge {
 
         Ok(Self {
             client,
+            credentials,
             runner,
             mode: config.mode.clone(),
             tenants,
             subjects: config.subjects.clone(),
             health,
+            max_activity_bytes: config.limits.max_activity_bytes,
         })
     }
 
+    /// Re-authenticates when `NATS_JWT` / `NATS_SEED` rotate; no-op without JWT auth.
+    pub fn watch_credentials(&self, watcher: &mut SecretWatcher) {
+        let Some(credentials) = self.credentials.clone() else {
+            return;
+        };
+        let client = self.client.clone();
+        let handle = Handle::current();
+        watcher.on_rotate(
+            &["NATS_JWT", "NATS_SEED", "NATS_NKEY_SEED"],
+            move |values: &RotatedSecrets| {
+                let Some(jwt) = values.get("NATS_JWT") else {
+                    tracing::warn!("NATS_JWT unavailable after rotation; keeping current credentials");
+                    return;
+                };
+                let Some(seed) = values
+                    .get("NATS_SEED")
+                    .or_else(|| values.get("NATS_NKEY_SEED"))
+                else {
+                    tracing::warn!("NATS_SEED unavailable after rotation; keeping current credentials");
+                    return;
+                };
+                if let Err(err) = nkeys::KeyPair::from_seed(seed) {
+                    tracing::error!(error = %err, "rotated NATS seed is invalid; keeping current credentials");
+                    return;
+                }
+                *credentials.write() = NatsCredentials {
+                    jwt: jwt.clone(),
+                    seed: seed.clone(),
+                };
+                let client = client.clone();
+                handle.spawn(async move {
+                    match client.force_reconnect().await {
+                        Ok(()) => tracing::info!("NATS re-authenticated with rotated credentials"),
+                        Err(err) => {
+                            tracing::error!(error = %err, "NATS reconnect after rotation failed")
+                        }
+                    }
+                });
+            },
+        );
+    }
+
     pub async fn run(self) -> Result<()> {
         if self.tenants.is_empty() {
             tracing::warn!("no packs registered; bridge idle");
@@ -66,6 +139,7 @@ impl NatsBridge {
             let egress_subject = self.subjects.egress_subject(tenant);
             let tenant_name = tenant.clone();
             let health = self.health.clone();
+            let max_activity_bytes = self.max_activity_bytes;
 
             join_set.spawn(async move {
                 let tenant = tenant_name;
@@ -76,6 +150,7 @@ impl NatsBridge {
                         &runner,
                         &client,
                         &health,
+                        max_activity_bytes,
                         message,
                     )
                     .await
@@ -123,8 +198,19 @@ async fn process_message(
     runner: &RunnerBridge,
     client: &Client,
     health: &HealthMonitor,
+    max_activity_bytes: usize,
     message: Message,
 ) -> Result<()> {
+    if message.payload.len() > max_activity_bytes {
+        tracing::warn!(
+            tenant = %tenant,
+            bytes = message.payload.len(),
+            limit = max_activity_bytes,
+            "activity payload exceeds max_activity_bytes"
+        );
+        health.record_failure(tenant);
+        return Ok(());
+    }
     let activity: Activity = match serde_json::from_slice(message.payload.as_ref()) {
         Ok(val) => val,
         Err(err) => {
@@ -172,24 +258,78 @@ async fn process_message(
     Ok(())
 }
 
-async fn connect_client(config: &crate::config::NatsConfig) -> Result<Client> {
-    let options = match &config.auth {
-        NatsAuth::None => ConnectOptions::new(),
-        NatsAuth::Jwt { jwt, seed } => {
-            let seed = Arc::new(seed.clone());
-            ConnectOptions::new().jwt(jwt.clone(), move |nonce: Vec<u8>| {
-                let seed = seed.clone();
-                async move {
-                    let key_pair = nkeys::KeyPair::from_seed(&seed).map_err(AuthError::new)?;
-                    key_pair.sign(&nonce).map_err(AuthError::new)
+/// Events queued for the audit publisher before new ones are dropped.
+const AUDIT_QUEUE: usize = 1024;
+
+/// Publishes secret access events to a NATS subject from a background task, so secret
+/// reads never wait on the network; events are dropped while the queue is full.
+struct NatsAuditSink {
+    events: mpsc::Sender<Vec<u8>>,
+}
+
+impl NatsAuditSink {
+    fn spawn(client: Client, subject: String) -> Self {
+        let (events, mut pending) = mpsc::channel::<Vec<u8>>(AUDIT_QUEUE);
+        tokio::spawn(async move {
+            while let Some(payload) = pending.recv().await {
+                if let Err(err) = client.publish(subject.clone(), payload.into()).await {
+                    tracing::warn!(subject = %subject, error = %err, "failed to publish secret audit event");
                 }
-            })
+            }
+        });
+        Self { events }
+    }
+}
+
+impl AuditSink for NatsAuditSink {
+    fn record(&self, event: &SecretAccess) {
+        match serde_json::to_vec(event) {
+            Ok(payload) => {
+                if let Err(mpsc::error::TrySendError::Full(_)) = self.events.try_send(payload) {
+                    tracing::warn!(
+                        secret = %event.secret,
+                        "secret audit queue full; dropping event"
+                    );
+                }
+            }
+            Err(err) => tracing::warn!(error = %err, "failed to encode secret audit event"),
         }
+    }
+}
+
+/// Client for `config` without credential rotation, e.g. for one-off publishes.
+pub async fn connect(config: &NatsConfig) -> Result<Client> {
+    connect_client(&config.url, shared_credentials(&config.auth)).await
+}
+
+fn shared_credentials(auth: &NatsAuth) -> Option<SharedCredentials> {
+    match auth {
+        NatsAuth::None => None,
+        NatsAuth::Jwt { jwt, seed } => Some(Arc::new(RwLock::new(NatsCredentials {
+            jwt: jwt.clone(),
+            seed: seed.clone(),
+        }))),
+    }
+}
+
+async fn connect_client(url: &str, credentials: Option<SharedCredentials>) -> Result<Client> {
+    let options = match credentials {
+        None => ConnectOptions::new(),
+        Some(credentials) => ConnectOptions::with_auth_callback(move |nonce: Vec<u8>| {
+            let NatsCredentials { jwt, seed } = credentials.read().clone();
+            async move {
+                let key_pair = nkeys::KeyPair::from_seed(&seed).map_err(AuthError::new)?;
+                let mut auth = Auth::new();
+                auth.jwt = Some(jwt);
+                auth.signature = Some(key_pair.sign(&nonce).map_err(AuthError::new)?);
+                Ok(auth)
+            }
+        }),
     };
 
     options
         .name("greentic-demo")
-        .connect(&config.url)
+        .connect(url)
         .await
-        .with_context(|| format!("failed to connect to {}", config.url))
+        .with_context(|| format!("failed to connect to {url}"))
 }
@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::gtpack::{Gtpack, find_manifest_root, is_gtpack};
+use crate::loader::TenantPack;
+use crate::pack_signing::PackDigest;
+
+const OBJECTS_DIR: &str = "sha256";
+const STATE_FILE: &str = "state.json";
+
+/// Content-addressed store (`sha256/<hex>/`) for downloaded and unpacked packs; `state.json`
+/// maps URLs to objects and tenants to the digests they were last served with.
+#[derive(Debug)]
+pub struct PackCache {
+    root: PathBuf,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct CacheState {
+    #[serde(default)]
+    tenants: BTreeMap<String, TenantDigests>,
+    #[serde(default)]
+    downloads: BTreeMap<String, Download>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Download {
+    digest: String,
+    file: String,
+}
+
+/// Digests a tenant is running: the main pack followed by its overlays.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantDigests {
+    pub digest: String,
+    #[serde(default)]
+    pub overlays: Vec<String>,
+}
+
+/// A pack ready for the runner, with the digest identifying its contents.
+#[derive(Debug, Clone)]
+pub struct OpenedPack {
+    pub entry: PathBuf,
+    pub gtpack: Option<Gtpack>,
+    pub digest: String,
+}
+
+impl PackCache {
+    pub fn open(root: &Path) -> Result<Self> {
+        fs::create_dir_all(root.join(OBJECTS_DIR))
+            .with_context(|| format!("failed to create pack cache {}", root.display()))?;
+        let state_path = root.join(STATE_FILE);
+        let state = match fs::read(&state_path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|err| {
+                tracing::warn!(path = %state_path.display(), error = %err, "ignoring corrupt pack cache state");
+                CacheState::default()
+            }),
+            Err(_) => CacheState::default(),
+        };
+        Ok(Self {
+            root: root.to_path_buf(),
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Stores a downloaded pack under its digest and returns the cached file.
+    pub fn store_download(&self, url: &Url, file_name: &str, bytes: &[u8]) -> Result<PathBuf> {
+        let hex = hex::encode(Sha256::digest(bytes));
+        let dir = self.object_dir(&hex);
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join(file_name);
+        if !path.exists() {
+            fs::write(&path, bytes)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        self.state.lock().downloads.insert(
+            url.to_string(),
+            Download {
+                digest: format!("sha256:{hex}"),
+                file: file_name.to_string(),
+            },
+        );
+        Ok(path)
+    }
+
+    /// Last download of `url`, if its object is still present.
+    pub fn cached_download(&self, url: &Url) -> Option<PathBuf> {
+        let state = self.state.lock();
+        let download = state.downloads.get(url.as_str())?;
+        let hex = download.digest.strip_prefix("sha256:")?;
+        let path = self.object_dir(hex).join(&download.file);
+        path.is_file().then_some(path)
+    }
+
+    /// Resolves `path` to something the runner loads, unpacking `.gtpack` archives into
+    /// the store once per content digest.
+    pub fn open_pack(&self, path: &Path) -> Result<OpenedPack> {
+        let is_archive = is_gtpack(path)
+            && path.is_file()
+            && path.file_name().and_then(|n| n.to_str()) != Some("manifest.yaml");
+
+        if is_gtpack(path) && !is_archive {
+            let pack = Gtpack::open(path, &self.root)?;
+            let root = pack.root().unwrap_or(path).to_path_buf();
+            return Ok(OpenedPack {
+                entry: pack.entry_path(),
+                digest: PackDigest::compute(&root)?.to_string(),
+                gtpack: Some(pack),
+            });
+        }
+
+        let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let hex = hex::encode(Sha256::digest(&bytes));
+        let digest = format!("sha256:{hex}");
+        if !is_archive {
+            return Ok(OpenedPack {
+                entry: path.to_path_buf(),
+                gtpack: None,
+                digest,
+            });
+        }
+
+        let unpacked = self.object_dir(&hex).join("unpacked");
+        let reused = match path.file_name() {
+            Some(name) if unpacked.join(name).is_dir() => find_manifest_root(&unpacked.join(name))?,
+            _ => None,
+        };
+        let pack = match reused {
+            Some(root) => {
+                tracing::debug!(pack = %path.display(), %digest, "reusing unpacked pack");
+                Gtpack::open(&root, &unpacked)?
+            }
+            None => Gtpack::open(path, &unpacked)?,
+        };
+        Ok(OpenedPack {
+            entry: pack.entry_path(),
+            gtpack: Some(pack),
+            digest,
+        })
+    }
+
+    /// Records the digests every served tenant runs and removes objects referenced by
+    /// neither this record nor the previous one.
+    pub fn record(&self, packs: &[TenantPack]) -> Result<()> {
+        let mut state = self.state.lock();
+        let previous = std::mem::replace(
+            &mut state.tenants,
+            packs
+                .iter()
+                .map(|pack| {
+                    (
+                        pack.tenant.clone(),
+                        TenantDigests {
+                            digest: pack.digest.clone(),
+                            overlays: pack.overlay_digests.clone(),
+                        },
+                    )
+                })
+                .collect(),
+        );
+
+        let referenced: BTreeSet<String> = state
+            .tenants
+            .values()
+            .chain(previous.values())
+            .flat_map(|tenant| std::iter::once(&tenant.digest).chain(&tenant.overlays))
+            .filter_map(|digest| digest.strip_prefix("sha256:"))
+            .map(str::to_string)
+            .collect();
+        state.downloads.retain(|_, download| {
+            download
+                .digest
+                .strip_prefix("sha256:")
+                .is_some_and(|hex| referenced.contains(hex))
+        });
+
+        let mut removed = 0usize;
+        for entry in fs::read_dir(self.root.join(OBJECTS_DIR))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if referenced.contains(&name) {
+                continue;
+            }
+            match fs::remove_dir_all(entry.path()) {
+                Ok(()) => removed += 1,
+                Err(err) => {
+                    tracing::warn!(object = %name, error = %err, "failed to evict cached pack")
+                }
+            }
+        }
+
+        let state_path = self.root.join(STATE_FILE);
+        fs::write(&state_path, serde_json::to_vec_pretty(&*state)?)
+            .with_context(|| format!("failed to write {}", state_path.display()))?;
+        tracing::info!(
+            cache = %self.root.display(),
+            tenants = state.tenants.len(),
+            evicted = removed,
+            "pack cache updated"
+        );
+        Ok(())
+    }
+
+    /// Digests recorded for `tenant` by the last [`PackCache::record`].
+    pub fn tenant_digests(&self, tenant: &str) -> Option<TenantDigests> {
+        self.state.lock().tenants.get(tenant).cloned()
+    }
+
+    fn object_dir(&self, hex: &str) -> PathBuf {
+        self.root.join(OBJECTS_DIR).join(hex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downloads_are_reused_and_unreferenced_objects_collected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let url = Url::parse("http://127.0.0.1:8000/packs/customera/index.ygtc").unwrap();
+
+        let cache = PackCache::open(root).unwrap();
+        let stale = cache.store_download(&url, "index.ygtc", b"old").unwrap();
+        let path = cache.store_download(&url, "index.ygtc", b"new").unwrap();
+        let opened = cache.open_pack(&path).unwrap();
+        assert_eq!(opened.entry, path);
+        assert!(path.starts_with(root.join(OBJECTS_DIR)));
+
+        let pack = TenantPack {
+            tenant: "customera".into(),
+            index_path: opened.entry.clone(),
+            bindings_path: root.join("bindings.yaml"),
+            bindings_source: root.join("bindings.yaml"),
+            overlays: Vec::new(),
+            manifest: None,
+            digest: opened.digest.clone(),
+            overlay_digests: Vec::new(),
+        };
+        cache.record(&[pack]).unwrap();
+        assert!(!stale.exists());
+        drop(cache);
+
+        let reopened = PackCache::open(root).unwrap();
+        assert_eq!(reopened.cached_download(&url), Some(path.clone()));
+        assert_eq!(
+            reopened.tenant_digests("customera").unwrap().digest,
+            opened.digest
+        );
+
+        // A tenant missing from one record keeps its object until the next one.
+        reopened.record(&[]).unwrap();
+        assert!(path.exists());
+        reopened.record(&[]).unwrap();
+        assert!(!path.exists());
+    }
+}
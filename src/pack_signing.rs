@@ -0,0 +1,304 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::Deserialize;
+use serde_yaml_bw as serde_yaml;
+use sha2::{Digest, Sha256};
+
+use crate::gtpack::{Gtpack, is_gtpack};
+
+/// Extension of the detached signature stored next to a pack (`index.ygtc.sig`).
+pub const SIGNATURE_EXTENSION: &str = "sig";
+
+/// Canonical SHA-256 digest of a pack: the hash of a sorted `sha256sum`-style listing of
+/// its manifest, flows and components, or of the single pack file.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PackDigest([u8; 32]);
+
+impl PackDigest {
+    /// Digest of the pack at `content`: a source `.gtpack` directory or a single pack file.
+    pub fn compute(content: &Path) -> Result<Self> {
+        let mut entries = Vec::new();
+        if content.is_dir() {
+            let pack = Gtpack::open(content, content)?;
+            let manifest = pack
+                .manifest()
+                .ok_or_else(|| anyhow!("{} is not a source pack", content.display()))?;
+            entries.push(PathBuf::from("manifest.yaml"));
+            entries.extend(manifest.flows.iter().map(|flow| flow.file.clone()));
+            let components = content.join("components");
+            if components.is_dir() {
+                for entry in fs::read_dir(&components)? {
+                    let path = entry?.path();
+                    if path.extension().and_then(|ext| ext.to_str()) == Some("wasm") {
+                        let name = path.file_name().expect("read_dir entries have names");
+                        entries.push(Path::new("components").join(name));
+                    }
+                }
+            }
+        } else {
+            let name = content
+                .file_name()
+                .ok_or_else(|| anyhow!("{} is not a pack file", content.display()))?;
+            entries.push(PathBuf::from(name));
+        }
+
+        let base = if content.is_dir() {
+            content
+        } else {
+            content.parent().unwrap_or_else(|| Path::new("."))
+        };
+        let mut listing: Vec<(String, String)> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let bytes = fs::read(base.join(&entry))
+                .with_context(|| format!("failed to read {}", base.join(&entry).display()))?;
+            let name = entry
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            listing.push((name, hex::encode(Sha256::digest(&bytes))));
+        }
+        listing.sort();
+        listing.dedup();
+
+        let mut hasher = Sha256::new();
+        for (name, hash) in &listing {
+            hasher.update(format!("{hash}  {name}\n"));
+        }
+        Ok(Self(hasher.finalize().into()))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for PackDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sha256:{}", hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for PackDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Detached signature path for a pack as shipped (directory, archive or file).
+pub fn signature_path(source: &Path) -> PathBuf {
+    let mut name = source
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".");
+    name.push(SIGNATURE_EXTENSION);
+    source.with_file_name(name)
+}
+
+/// Directory or file whose contents are digested for `source`, unpacking archives into
+/// `scratch` when needed.
+pub fn pack_content(source: &Path, scratch: &Path) -> Result<PathBuf> {
+    if !is_gtpack(source) {
+        return Ok(source.to_path_buf());
+    }
+    let pack = Gtpack::open(source, scratch)?;
+    Ok(pack
+        .root()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| pack.entry_path()))
+}
+
+/// Signs `content` with the base64 Ed25519 seed stored at `key_path`, returning the
+/// base64 signature and the matching public key.
+pub fn sign(content: &Path, key_path: &Path) -> Result<(String, String, PackDigest)> {
+    let raw = fs::read_to_string(key_path)
+        .with_context(|| format!("failed to read signing key {}", key_path.display()))?;
+    let seed: [u8; 32] = decode_key(raw.trim())
+        .with_context(|| format!("invalid signing key in {}", key_path.display()))?;
+    let key = SigningKey::from_bytes(&seed);
+    let digest = PackDigest::compute(content)?;
+    let signature = key.sign(digest.as_bytes());
+    Ok((
+        BASE64.encode(signature.to_bytes()),
+        BASE64.encode(key.verifying_key().to_bytes()),
+        digest,
+    ))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BindingsSecurity {
+    #[serde(default)]
+    mcp: McpSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct McpSection {
+    #[serde(default)]
+    security: SecuritySection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SecuritySection {
+    #[serde(default)]
+    require_signature: bool,
+}
+
+/// Enforces detached pack signatures when `PACK_PUBLIC_KEY` is set or bindings require them.
+#[derive(Clone, Default)]
+pub struct PackVerifier {
+    key: Option<VerifyingKey>,
+}
+
+impl fmt::Debug for PackVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PackVerifier")
+            .field("key", &self.key.map(|key| BASE64.encode(key.to_bytes())))
+            .finish()
+    }
+}
+
+impl PackVerifier {
+    /// Builds a verifier from a base64 Ed25519 public key, optionally prefixed with
+    /// `ed25519:`; `None` or blank disables it.
+    pub fn from_key(public_key: Option<&str>) -> Result<Self> {
+        let Some(raw) = public_key.map(str::trim).filter(|raw| !raw.is_empty()) else {
+            return Ok(Self::default());
+        };
+        let bytes: [u8; 32] = decode_key(raw).context("invalid PACK_PUBLIC_KEY")?;
+        let key = VerifyingKey::from_bytes(&bytes).context("invalid PACK_PUBLIC_KEY")?;
+        Ok(Self { key: Some(key) })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Whether `bindings_path` opts its tenant into mandatory signatures.
+    pub fn required_by(bindings_path: &Path) -> Result<bool> {
+        let contents = fs::read_to_string(bindings_path)
+            .with_context(|| format!("failed to read {}", bindings_path.display()))?;
+        let bindings: BindingsSecurity = serde_yaml::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", bindings_path.display()))?;
+        Ok(bindings.mcp.security.require_signature)
+    }
+
+    /// Verifies every `(source, content)` pack of `tenant`; `source` locates the
+    /// `.sig` file and `content` is what gets digested (see [`pack_content`]).
+    pub fn verify_tenant(
+        &self,
+        tenant: &str,
+        bindings_path: &Path,
+        packs: &[(PathBuf, PathBuf)],
+    ) -> Result<()> {
+        let required = self.enabled() || Self::required_by(bindings_path)?;
+        if !required {
+            return Ok(());
+        }
+        let Some(key) = &self.key else {
+            bail!(
+                "tenant {tenant} sets mcp.security.require_signature but PACK_PUBLIC_KEY is not configured"
+            );
+        };
+        for (source, content) in packs {
+            let digest = verify_pack(key, source, content)
+                .with_context(|| format!("signature check failed for tenant {tenant}"))?;
+            tracing::info!(tenant = %tenant, pack = %source.display(), %digest, "pack signature verified");
+        }
+        Ok(())
+    }
+}
+
+fn verify_pack(key: &VerifyingKey, source: &Path, content: &Path) -> Result<PackDigest> {
+    let sig_path = signature_path(source);
+    let raw = fs::read_to_string(&sig_path)
+        .with_context(|| format!("missing signature {}", sig_path.display()))?;
+    let bytes: [u8; 64] = BASE64
+        .decode(raw.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("{} is not a base64 Ed25519 signature", sig_path.display()))?;
+    let digest = PackDigest::compute(content)?;
+    key.verify_strict(digest.as_bytes(), &Signature::from_bytes(&bytes))
+        .map_err(|_| {
+            anyhow!(
+                "signature {} does not match {} ({digest})",
+                sig_path.display(),
+                source.display()
+            )
+        })?;
+    Ok(digest)
+}
+
+fn decode_key<const N: usize>(raw: &str) -> Result<[u8; N]> {
+    let raw = raw.strip_prefix("ed25519:").unwrap_or(raw);
+    let bytes = BASE64
+        .decode(raw)
+        .context("expected a base64-encoded Ed25519 key")?;
+    let len = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("expected {N} key bytes, got {len}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_pack_verifies_and_tampering_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let tenant_dir = root.join("customera");
+        fs::create_dir_all(&tenant_dir).unwrap();
+        let pack = tenant_dir.join("index.ygtc");
+        fs::write(&pack, "id: messaging\n").unwrap();
+        let bindings = tenant_dir.join("bindings.yaml");
+        fs::write(&bindings, "tenant: customera\n").unwrap();
+        let key_path = root.join("signing.key");
+        fs::write(&key_path, BASE64.encode([7u8; 32])).unwrap();
+
+        let (signature, public_key, _) = sign(&pack, &key_path).unwrap();
+        fs::write(signature_path(&pack), signature).unwrap();
+        let verifier = PackVerifier::from_key(Some(&public_key)).unwrap();
+        let packs = [(pack.clone(), pack.clone())];
+        verifier
+            .verify_tenant("customera", &bindings, &packs)
+            .unwrap();
+        PackVerifier::from_key(Some(&format!("ed25519:{public_key}")))
+            .unwrap()
+            .verify_tenant("customera", &bindings, &packs)
+            .unwrap();
+
+        fs::write(&pack, "id: tampered\n").unwrap();
+        let err = verifier
+            .verify_tenant("customera", &bindings, &packs)
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("does not match"));
+
+        fs::write(
+            &bindings,
+            "tenant: customera\nmcp:\n  security:\n    require_signature: true\n",
+        )
+        .unwrap();
+        let err = PackVerifier::default()
+            .verify_tenant("customera", &bindings, &packs)
+            .unwrap_err();
+        assert!(err.to_string().contains("PACK_PUBLIC_KEY"));
+    }
+
+    #[test]
+    fn source_pack_digest_is_stable() {
+        let pack =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/deployment/generic-deploy.gtpack");
+        let first = PackDigest::compute(&pack).unwrap();
+        assert_eq!(first, PackDigest::compute(&pack).unwrap());
+        assert!(first.to_string().starts_with("sha256:"));
+    }
+}
@@ -1,96 +1,123 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Context, Result, anyhow};
 use greentic_runner_host::config::HostConfig;
-use greentic_runner_host::pack::PackRuntime;
-use greentic_runner_host::runner::engine::{FlowContext, FlowEngine, FlowExecution, RetryConfig};
-use greentic_runner_host::secrets::SecretsBackend;
+use greentic_runner_host::runner::engine::{FlowContext, RetryConfig};
 use serde_json::{Value, json};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::bindings;
 use crate::config::Mode;
+pub use crate::flow_engine::FlowConflict;
+use crate::flow_engine::TenantFlows;
+use crate::flow_mocks::{MockSpec, TenantMocks};
+use crate::flow_observer::{FlowAuditLog, FlowObserver};
+use crate::hint_policy::{HintPolicy, RoutingHints};
 use crate::loader::TenantPack;
+use crate::secret_policy::SecretPolicy;
 use crate::types::{Activity, ActivityType};
-use greentic_runner_host::storage::{new_session_store, new_state_store};
-use greentic_runner_host::wasi::RunnerWasiPolicy;
 
 #[derive(Clone)]
 pub struct RunnerBridge {
     mode: Mode,
-    allowed_secrets: Vec<String>,
+    secret_policy: SecretPolicy,
     tenants: Arc<RwLock<HashMap<String, Arc<TenantRuntime>>>>,
+    audit: Option<FlowAuditLog>,
 }
 
 struct TenantRuntime {
     tenant: String,
     config: Arc<HostConfig>,
-    engine: Arc<FlowEngine>,
+    flows: TenantFlows,
     messaging_flow_id: String,
+    hint_policy: HintPolicy,
+    rejected_hints: AtomicU64,
+    mocks: Option<TenantMocks>,
+    pack_digest: String,
 }
 
 impl RunnerBridge {
-    pub fn new(mode: Mode, allowed_secrets: Vec<String>) -> Self {
+    pub fn new(mode: Mode, secret_policy: SecretPolicy) -> Self {
         Self {
             mode,
-            allowed_secrets,
+            secret_policy,
             tenants: Arc::new(RwLock::new(HashMap::new())),
+            audit: None,
         }
     }
 
+    /// Appends per-node JSON Lines audit records for every executed activity to `path`.
+    pub fn with_flow_audit(mut self, path: &Path) -> Result<Self> {
+        self.audit = Some(FlowAuditLog::open(path)?);
+        Ok(self)
+    }
+
     pub async fn register_pack(&self, pack: &TenantPack) -> Result<()> {
+        self.register(pack, None).await
+    }
+
+    /// Test harness entry point: registers `pack` with the mocks in `mocks_path`.
+    pub async fn register_pack_with_mocks(
+        &self,
+        pack: &TenantPack,
+        mocks_path: &Path,
+    ) -> Result<()> {
+        let spec = MockSpec::load(mocks_path)?;
+        let mocks = TenantMocks::prepare(&pack.tenant, spec)?;
+        self.register(pack, Some(mocks)).await
+    }
+
+    async fn register(&self, pack: &TenantPack, mocks: Option<TenantMocks>) -> Result<()> {
         tracing::info!(
             tenant = %pack.tenant,
             path = %pack.index_path.display(),
             bindings = %pack.bindings_path.display(),
+            digest = %pack.digest,
             "registering pack"
         );
+        let resolved = match &mocks {
+            Some(mocks) => {
+                bindings::resolve_with_secrets(&pack.bindings_path, &pack.tenant, mocks.secrets())
+            }
+            None => bindings::resolve(&pack.bindings_path, &pack.tenant, &self.secret_policy),
+        };
+        let resolved =
+            resolved.with_context(|| format!("failed to load bindings for {}", pack.tenant))?;
+        let hint_policy = HintPolicy::from_bindings(&resolved, &pack.tenant)
+            .with_context(|| format!("invalid routing_hints for {}", pack.tenant))?;
         let config = Arc::new(
-            HostConfig::load_from_path(&pack.bindings_path)
+            bindings::host_config_from(&pack.bindings_path, resolved)
                 .with_context(|| format!("failed to load bindings for {}", pack.tenant))?,
         );
-        ensure_allowed_secrets(&config, &self.allowed_secrets)?;
-
-        let session_store = new_session_store();
-        let state_store = new_state_store();
-        let wasi_policy = Arc::new(RunnerWasiPolicy::default());
-        let secrets_backend = SecretsBackend::from_env(std::env::var("SECRETS_BACKEND").ok())?;
-        let secrets_manager = secrets_backend.build_manager()?;
-
-        let pack_runtime = Arc::new(
-            PackRuntime::load(
-                &pack.index_path,
-                Arc::clone(&config),
-                None,
-                None,
-                Some(Arc::clone(&session_store)),
-                Some(Arc::clone(&state_store)),
-                Arc::clone(&wasi_policy),
-                Arc::clone(&secrets_manager),
-                false,
-            )
-            .await
-            .with_context(|| format!("failed to load pack for {}", pack.tenant))?,
-        );
-
-        let engine = Arc::new(
-            FlowEngine::new(vec![Arc::clone(&pack_runtime)], Arc::clone(&config))
-                .await
-                .with_context(|| format!("failed to prime flow engine for {}", pack.tenant))?,
-        );
+        self.secret_policy.check(&config)?;
+        tracing::info!(tenant = %pack.tenant, policy = hint_policy.mode(), "routing hint policy");
 
-        let messaging_flow = engine
+        let flows = TenantFlows::load(&pack.tenant, &pack_paths(pack))?;
+        let messaging_flow = flows
             .flow_by_type("messaging")
             .ok_or_else(|| anyhow!("tenant {} has no messaging flow", pack.tenant))?
             .id
             .clone();
 
+        if let Some(mocks) = &mocks {
+            mocks
+                .bind_nodes(&flows)
+                .with_context(|| format!("invalid node mocks for {}", pack.tenant))?;
+        }
+
         let runtime = Arc::new(TenantRuntime {
             tenant: pack.tenant.clone(),
             config,
-            engine,
+            flows,
             messaging_flow_id: messaging_flow,
+            hint_policy,
+            rejected_hints: AtomicU64::new(0),
+            mocks,
+            pack_digest: pack.digest.clone(),
         });
 
         self.tenants
@@ -112,6 +139,8 @@ impl RunnerBridge {
         let payload = activity_to_flow_input(&activity)?;
         let selection = select_flow(&runtime, &activity);
         let retry_cfg = runtime.config.mcp_retry_config();
+        let activity_id = activity.activity_id().unwrap_or("unknown");
+        let observer = FlowObserver::new(tenant, activity_id, self.audit.clone());
         let ctx = FlowContext {
             tenant: &runtime.tenant,
             flow_id: selection.flow_id.as_str(),
@@ -121,43 +150,61 @@ impl RunnerBridge {
             session_id: None,
             provider_id: None,
             retry_config: RetryConfig::from(retry_cfg),
-            observer: None,
-            mocks: None,
+            observer: Some(&observer),
+            mocks: runtime.mocks.as_ref().map(|m| m.layer().as_ref()),
         };
 
         tracing::debug!(
             tenant,
             mode = ?self.mode,
             flow = %selection.flow_id,
-            "dispatching activity to flow engine"
+            pack_digest = %runtime.pack_digest,
+            "dispatching activity to flow"
         );
-        let response: FlowExecution = runtime
-            .engine
-            .execute(ctx, payload)
-            .await
-            .with_context(|| format!("flow execution failed for tenant {tenant}"))?;
+        let result = runtime.flows.execute(&ctx, payload);
+        let failed_node = observer.finish(result.as_ref().err());
+        let output = result.with_context(|| match failed_node {
+            Some((flow, node)) => {
+                format!("flow execution failed for tenant {tenant} at {flow}/{node}")
+            }
+            None => format!("flow execution failed for tenant {tenant}"),
+        })?;
 
-        flow_value_to_activities(&activity, tenant, response.output)
+        let mut responses = flow_value_to_activities(&activity, tenant, output)?;
+        let derived_ids = runtime.mocks.is_some().then_some(activity_id);
+        assign_missing_ids(&mut responses, derived_ids);
+        Ok(responses)
     }
-}
 
-fn ensure_allowed_secrets(config: &HostConfig, allowed: &[String]) -> Result<()> {
-    if allowed.is_empty() {
-        return Ok(());
+    /// Flows redefined by overlay packs when the tenant was registered.
+    pub async fn flow_conflicts(&self, tenant: &str) -> Option<Vec<FlowConflict>> {
+        let guard = self.tenants.read().await;
+        guard
+            .get(tenant)
+            .map(|runtime| runtime.flows.conflicts().to_vec())
     }
-    let binding = config
-        .messaging_binding()
-        .ok_or_else(|| anyhow!("bindings missing flow_type_bindings.messaging"))?;
 
-    for secret in allowed {
-        if !binding.secrets.iter().any(|s| s == secret) {
-            bail!(
-                "tenant {} binding must include secret `{secret}` under flow_type_bindings.messaging.secrets",
-                config.tenant
-            );
-        }
+    /// Content digest of the main pack the tenant is running.
+    pub async fn pack_digest(&self, tenant: &str) -> Option<String> {
+        let guard = self.tenants.read().await;
+        guard.get(tenant).map(|runtime| runtime.pack_digest.clone())
+    }
+
+    /// Number of routing hints rejected by the tenant's policy since registration.
+    pub async fn rejected_hints(&self, tenant: &str) -> Option<u64> {
+        let guard = self.tenants.read().await;
+        guard
+            .get(tenant)
+            .map(|runtime| runtime.rejected_hints.load(Ordering::Relaxed))
     }
-    Ok(())
+}
+
+/// Main pack followed by overlays.
+pub fn pack_paths(pack: &TenantPack) -> Vec<PathBuf> {
+    std::iter::once(pack.index_path.as_path())
+        .chain(pack.overlays.iter().map(Path::new))
+        .map(Path::to_path_buf)
+        .collect()
 }
 
 struct FlowSelection {
@@ -176,17 +223,41 @@ fn select_flow(runtime: &TenantRuntime, activity: &Activity) -> FlowSelection {
         );
     }
 
-    let node_hint = resolve_node_hint(activity);
-    if let Some(flow_hint) = resolve_flow_hint(activity) {
-        if runtime.engine.flow_by_id(&flow_hint).is_some() {
+    let hints = RoutingHints {
+        flow: resolve_flow_hint(activity),
+        node: resolve_node_hint(activity),
+        signature: resolve_hint_signature(activity),
+        activity_id: activity.id.clone(),
+        timestamp: activity.timestamp,
+    };
+    if let Err(rejection) = runtime.hint_policy.check(&runtime.tenant, &hints) {
+        let rejected = runtime.rejected_hints.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::warn!(
+            tenant = %runtime.tenant,
+            flow = ?hints.flow,
+            node = ?hints.node,
+            policy = runtime.hint_policy.mode(),
+            reason = %rejection,
+            rejected,
+            "routing hint rejected; using messaging flow"
+        );
+        return FlowSelection {
+            flow_id: runtime.messaging_flow_id.clone(),
+            node: None,
+        };
+    }
+
+    let node_hint = hints.node;
+    if let Some(flow_hint) = hints.flow {
+        if runtime.flows.flow_by_id(&flow_hint).is_some() {
             tracing::debug!(
                 tenant = %runtime.tenant,
                 flow = %flow_hint,
                 "using flow override from activity"
             );
             return FlowSelection {
+                node: node_in_flow(runtime, &flow_hint, node_hint),
                 flow_id: flow_hint,
-                node: node_hint,
             };
         } else {
             tracing::warn!(
@@ -198,11 +269,29 @@ fn select_flow(runtime: &TenantRuntime, activity: &Activity) -> FlowSelection {
     }
 
     FlowSelection {
+        node: node_in_flow(runtime, &runtime.messaging_flow_id, node_hint),
         flow_id: runtime.messaging_flow_id.clone(),
-        node: node_hint,
     }
 }
 
+fn node_in_flow(runtime: &TenantRuntime, flow_id: &str, node: Option<String>) -> Option<String> {
+    let node = node?;
+    let known = runtime
+        .flows
+        .flow_by_id(flow_id)
+        .is_some_and(|flow| flow.nodes.contains_key(&node));
+    if known {
+        return Some(node);
+    }
+    tracing::warn!(
+        tenant = %runtime.tenant,
+        flow = %flow_id,
+        node = %node,
+        "activity requested node not found; starting at the flow's start node"
+    );
+    None
+}
+
 fn resolve_flow_hint(activity: &Activity) -> Option<String> {
     channel_string(activity, &["flowId", "flow_id", "flow"])
         .or_else(|| session_string(activity, &["flow", "flowId", "flow_id"]))
@@ -213,6 +302,11 @@ fn resolve_node_hint(activity: &Activity) -> Option<String> {
         .or_else(|| session_string(activity, &["node", "nodeId", "node_id"]))
 }
 
+fn resolve_hint_signature(activity: &Activity) -> Option<String> {
+    channel_string(activity, &["hintSignature", "hint_signature"])
+        .or_else(|| session_string(activity, &["hintSignature", "hint_signature"]))
+}
+
 fn channel_string(activity: &Activity, keys: &[&str]) -> Option<String> {
     let map = activity.channel_data.as_ref()?.as_object()?;
     for key in keys {
@@ -300,6 +394,18 @@ fn coerce_activity(reference: &Activity, tenant: &str, value: Value) -> Result<A
     }
 }
 
+/// Gives id-less outgoing activities a random id, or `<incoming id>-<n>` with `derive_from`.
+fn assign_missing_ids(activities: &mut [Activity], derive_from: Option<&str>) {
+    for (index, activity) in activities.iter_mut().enumerate() {
+        if activity.id.is_none() {
+            activity.id = Some(match derive_from {
+                Some(reference) => format!("{reference}-{}", index + 1),
+                None => Uuid::new_v4().to_string(),
+            });
+        }
+    }
+}
+
 fn default_activity(reference: &Activity, tenant: &str, text: Option<String>) -> Activity {
     let mut activity = Activity {
         activity_type: ActivityType::Message,
@@ -314,9 +420,6 @@ fn normalize_outgoing(reference: &Activity, tenant: &str, activity: &mut Activit
     if matches!(activity.activity_type, ActivityType::Unknown(_)) {
         activity.activity_type = ActivityType::Message;
     }
-    if activity.id.is_none() {
-        activity.id = Some(Uuid::new_v4().to_string());
-    }
     if activity.conversation.is_none() {
         activity.conversation = reference.conversation.clone();
     }
@@ -408,6 +511,17 @@ mod tests {
         assert_eq!(responses[1].text.as_deref(), Some("two"));
     }
 
+    #[test]
+    fn mocked_replies_get_derived_ids() {
+        let incoming = base_activity();
+        let mut responses =
+            flow_value_to_activities(&incoming, "customera", json!(["one", { "id": "keep" }]))
+                .unwrap();
+        assign_missing_ids(&mut responses, Some("abc"));
+        assert_eq!(responses[0].id.as_deref(), Some("abc-1"));
+        assert_eq!(responses[1].id.as_deref(), Some("keep"));
+    }
+
     #[test]
     fn flow_hint_detected_from_channel_data() {
         let mut activity = base_activity();
@@ -421,4 +535,112 @@ mod tests {
         activity.channel_data = Some(json!({ "session": { "node": "qa_node" } }));
         assert_eq!(resolve_node_hint(&activity).as_deref(), Some("qa_node"));
     }
+
+    #[test]
+    fn hint_signature_detected_from_channel_data() {
+        let mut activity = base_activity();
+        activity.channel_data = Some(json!({ "flowId": "weather_bot", "hintSignature": "ab12" }));
+        assert_eq!(resolve_hint_signature(&activity).as_deref(), Some("ab12"));
+    }
+
+    #[tokio::test]
+    async fn repo_pack_replies_through_its_flow() {
+        let dir = tempfile::tempdir().unwrap();
+        let packs = Path::new(env!("CARGO_MANIFEST_DIR")).join("packs");
+        let report = crate::loader::load_packs(
+            &packs,
+            &dir.path().join("cache"),
+            &crate::pack_signing::PackVerifier::default(),
+        )
+        .unwrap();
+        let bridge = RunnerBridge::new(Mode::Dev, SecretPolicy::default());
+        for pack in &report.tenants {
+            bridge.register_pack(pack).await.unwrap();
+        }
+
+        let mut activity = base_activity();
+        activity.text = Some("hi".into());
+        let replies = bridge.handle_activity("customera", activity).await.unwrap();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].text.as_deref(), Some("Customer A received: hi"));
+        assert_eq!(replies[0].reply_to_id.as_deref(), Some("abc"));
+        assert_eq!(
+            replies[0].channel_data.as_ref().unwrap()["traceId"],
+            "trace-123"
+        );
+    }
+
+    #[tokio::test]
+    async fn mocked_tools_and_nodes_answer_deterministically() {
+        let dir = tempfile::tempdir().unwrap();
+        let packs = Path::new(env!("CARGO_MANIFEST_DIR")).join("packs");
+        let report = crate::loader::load_packs(
+            &packs,
+            &dir.path().join("cache"),
+            &crate::pack_signing::PackVerifier::default(),
+        )
+        .unwrap();
+        let mut pack = report
+            .tenants
+            .iter()
+            .find(|pack| pack.tenant == "customera")
+            .unwrap()
+            .clone();
+        let overlay = dir.path().join("weather.ygtc");
+        std::fs::write(
+            &overlay,
+            r#"id: customera-messaging
+type: messaging
+start: forecast
+nodes:
+  forecast:
+    mcp.exec:
+      component: weather.api
+      action: forecast
+    routing:
+      - to: alerts
+  alerts:
+    mcp.exec:
+      component: weather.api
+      action: alerts
+    routing:
+      - to: reply
+  reply:
+    emit.reply:
+      text: "{{nodes.forecast.payload.text}}, {{nodes.alerts.payload.text}}"
+    routing:
+      - out: true
+"#,
+        )
+        .unwrap();
+        pack.overlays.push(overlay);
+        let mocks = dir.path().join("mocks.yaml");
+        std::fs::write(
+            &mocks,
+            "tools:\n  weather.api:\n    forecast: { text: sunny }\nnodes:\n  alerts: { text: no alerts }\nsecrets:\n  TELEGRAM_BOT_TOKEN: test-token\n",
+        )
+        .unwrap();
+
+        let bridge = RunnerBridge::new(Mode::Dev, SecretPolicy::default());
+        bridge
+            .register_pack_with_mocks(&pack, &mocks)
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            let replies = bridge
+                .handle_activity("customera", base_activity())
+                .await
+                .unwrap();
+            assert_eq!(replies.len(), 1);
+            assert_eq!(replies[0].text.as_deref(), Some("sunny, no alerts"));
+            assert_eq!(replies[0].id.as_deref(), Some("abc-1"));
+        }
+
+        std::fs::write(&mocks, "nodes:\n  reply: { text: canned }\n").unwrap();
+        let err = bridge
+            .register_pack_with_mocks(&pack, &mocks)
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("only mcp.exec nodes can be mocked"));
+    }
 }
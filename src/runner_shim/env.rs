@@ -0,0 +1,385 @@
+//! Runner environment validated before tenants are discovered, whichever runner serves them.
+
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use thiserror::Error;
+
+use crate::duration::parse_duration;
+use crate::loader::{TenantPack, discover_tenants, record_served};
+use crate::pack_signing::PackVerifier;
+use crate::secret_policy::SecretPolicy;
+
+/// The runner environment variables, parsed and checked together.
+#[derive(Clone, Debug)]
+pub struct RunnerEnv {
+    pub port: u16,
+    pub secrets_backend: SecretsBackendKind,
+    pub pack_source: PackSource,
+    pub packs_dir: PathBuf,
+    /// `None` scans `packs_dir`, like `greentic-demo validate`.
+    pub pack_index_url: Option<String>,
+    pub pack_cache_dir: PathBuf,
+    pub pack_public_key: Option<String>,
+    pub pack_refresh_interval: Duration,
+    /// `PACKS_STRICT`: fail on any tenant load problem instead of skipping the tenant.
+    pub packs_strict: bool,
+    pub tenant_resolver: TenantResolver,
+    /// `DEFAULT_TENANT`: tenant for `TENANT_RESOLVER=env` and requests the resolver can't place.
+    pub default_tenant: String,
+    pub secret_policy: SecretPolicy,
+}
+
+/// Why [`RunnerEnv::from_env`] rejected the environment.
+#[derive(Debug, Error)]
+#[error("invalid runner environment:{}", list_problems(.0))]
+pub struct InvalidEnv(pub Vec<EnvProblem>);
+
+/// One environment variable that failed validation.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{var}={value:?}: {reason}")]
+pub struct EnvProblem {
+    pub var: &'static str,
+    pub value: String,
+    pub reason: String,
+}
+
+fn list_problems(problems: &[EnvProblem]) -> String {
+    problems
+        .iter()
+        .map(|problem| format!("\n  {problem}"))
+        .collect()
+}
+
+/// `SECRETS_BACKEND`: which secrets backend to bootstrap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecretsBackendKind {
+    Env,
+    Aws,
+    Gcp,
+    Azure,
+}
+
+impl FromStr for SecretsBackendKind {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "env" => Ok(Self::Env),
+            "aws" => Ok(Self::Aws),
+            "gcp" => Ok(Self::Gcp),
+            "azure" => Ok(Self::Azure),
+            _ => Err(expected(&["env", "aws", "gcp", "azure"])),
+        }
+    }
+}
+
+/// `PACK_SOURCE`: resolver scheme for tenant packs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackSource {
+    Fs,
+    Http,
+    Oci,
+    S3,
+    Gcs,
+    Azblob,
+}
+
+impl FromStr for PackSource {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "fs" => Ok(Self::Fs),
+            "http" => Ok(Self::Http),
+            "oci" => Ok(Self::Oci),
+            "s3" => Ok(Self::S3),
+            "gcs" => Ok(Self::Gcs),
+            "azblob" => Ok(Self::Azblob),
+            _ => Err(expected(&["fs", "http", "oci", "s3", "gcs", "azblob"])),
+        }
+    }
+}
+
+/// `TENANT_RESOLVER`: how requests are routed to tenants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TenantResolver {
+    Host,
+    Header,
+    Jwt,
+    Env,
+}
+
+impl FromStr for TenantResolver {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "host" => Ok(Self::Host),
+            "header" => Ok(Self::Header),
+            "jwt" => Ok(Self::Jwt),
+            "env" => Ok(Self::Env),
+            _ => Err(expected(&["host", "header", "jwt", "env"])),
+        }
+    }
+}
+
+fn expected(values: &[&str]) -> String {
+    format!("expected one of {}", values.join(", "))
+}
+
+impl RunnerEnv {
+    pub fn from_env() -> Result<Self, InvalidEnv> {
+        Self::from_lookup(|key| env::var(key).ok())
+    }
+
+    /// Validates every variable and reports all problems together rather than the first.
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, InvalidEnv> {
+        let mut vars = Vars {
+            lookup,
+            problems: Vec::new(),
+        };
+
+        let port = vars.parse("PORT", 8080, parse_port);
+        let secrets_backend = vars.parse("SECRETS_BACKEND", SecretsBackendKind::Env, str::parse);
+        let pack_source = vars.parse("PACK_SOURCE", PackSource::Fs, str::parse);
+        let packs_dir = vars.parse("PACKS_DIR", PathBuf::from("./packs"), parse_dir);
+        let pack_index_url = vars.parse("PACK_INDEX_URL", None, |raw| {
+            parse_index_location(raw).map(Some)
+        });
+        let pack_cache_dir = vars.parse("PACK_CACHE_DIR", PathBuf::from(".packs"), parse_dir);
+        let pack_public_key = vars.parse("PACK_PUBLIC_KEY", None, |raw| {
+            PackVerifier::from_key(Some(raw))
+                .map(|_| Some(raw.trim().to_string()))
+                .map_err(|err| format!("{err:#}"))
+        });
+        // `PACK_REFRESH_INTERVAL`, then the legacy `PACK_REFRESH_INTERVAL_SECS`.
+        let pack_refresh_interval = match vars.get("PACK_REFRESH_INTERVAL") {
+            Some(_) => vars.parse("PACK_REFRESH_INTERVAL", Duration::ZERO, parse_interval),
+            None => vars.parse(
+                "PACK_REFRESH_INTERVAL_SECS",
+                Duration::from_secs(30),
+                parse_interval,
+            ),
+        };
+        let packs_strict = vars.parse("PACKS_STRICT", false, parse_flag);
+        let tenant_resolver = vars.parse("TENANT_RESOLVER", TenantResolver::Host, str::parse);
+        let default_tenant = vars
+            .get("DEFAULT_TENANT")
+            .map(|tenant| tenant.trim().to_string())
+            .unwrap_or_else(|| "demo".into());
+        let allowed = list(vars.get("RUNNER_ALLOWED_SECRETS"), ',');
+        let secret_policy =
+            vars.parse("RUNNER_SECRET_GRANTS", SecretPolicy::new(&allowed), |raw| {
+                SecretPolicy::from_args(&allowed, &list(Some(raw.to_string()), ';'))
+                    .map_err(|err| err.to_string())
+            });
+
+        if !vars.problems.is_empty() {
+            return Err(InvalidEnv(vars.problems));
+        }
+        Ok(Self {
+            port,
+            secrets_backend,
+            pack_source,
+            packs_dir,
+            pack_index_url,
+            pack_cache_dir,
+            pack_public_key,
+            pack_refresh_interval,
+            packs_strict,
+            tenant_resolver,
+            default_tenant,
+            secret_policy,
+        })
+    }
+
+    /// Loads the tenants `PACKS_DIR` or `PACK_INDEX_URL` point at and records them as served;
+    /// `strict` is [`RunnerEnv::packs_strict`] at startup and `false` when refreshing.
+    pub async fn discover(&self, strict: bool) -> Result<Vec<TenantPack>> {
+        let verifier = PackVerifier::from_key(self.pack_public_key.as_deref())?;
+        let tenants = discover_tenants(
+            &self.packs_dir,
+            self.pack_index_url.as_deref(),
+            &self.pack_cache_dir,
+            &verifier,
+        )
+        .await
+        .with_context(|| format!("failed to load tenant packs from {}", self.pack_location()))?
+        .into_tenants(strict)?;
+        if tenants.is_empty() {
+            bail!(
+                "no tenant bindings found in {}; add at least one pack",
+                self.pack_location()
+            );
+        }
+        record_served(&self.pack_cache_dir, &tenants)?;
+        Ok(tenants)
+    }
+
+    fn pack_location(&self) -> String {
+        self.pack_index_url
+            .clone()
+            .unwrap_or_else(|| self.packs_dir.display().to_string())
+    }
+}
+
+/// Reads variables through `lookup`, treating blank values as unset and collecting
+/// every parse failure.
+struct Vars<F> {
+    lookup: F,
+    problems: Vec<EnvProblem>,
+}
+
+impl<F: Fn(&str) -> Option<String>> Vars<F> {
+    fn get(&self, var: &str) -> Option<String> {
+        (self.lookup)(var).filter(|value| !value.trim().is_empty())
+    }
+
+    fn parse<T>(
+        &mut self,
+        var: &'static str,
+        default: T,
+        parse: impl FnOnce(&str) -> Result<T, String>,
+    ) -> T {
+        let Some(value) = self.get(var) else {
+            return default;
+        };
+        parse(&value).unwrap_or_else(|reason| {
+            self.problems.push(EnvProblem { var, value, reason });
+            default
+        })
+    }
+}
+
+fn list(raw: Option<String>, delimiter: char) -> Vec<String> {
+    raw.map(|raw| raw.split(delimiter).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn parse_flag(raw: &str) -> Result<bool, String> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err("expected true or false".into()),
+    }
+}
+
+fn parse_port(raw: &str) -> Result<u16, String> {
+    match raw.trim().parse::<u32>() {
+        Ok(port @ 1..=65535) => Ok(port as u16),
+        Ok(_) => Err("port must be between 1 and 65535".into()),
+        Err(_) => Err("expected a port number".into()),
+    }
+}
+
+/// `PACK_INDEX_URL`: an `http(s)://` or `fs://` URL, or a local path.
+fn parse_index_location(raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    if raw.contains("://") {
+        let url = url::Url::parse(raw).map_err(|err| format!("invalid URL: {err}"))?;
+        match url.scheme() {
+            "http" | "https" if url.host_str().is_none_or(str::is_empty) => {
+                Err("URL has no host".into())
+            }
+            "http" | "https" | "fs" => Ok(raw.to_string()),
+            scheme => Err(format!(
+                "unsupported scheme `{scheme}` (expected http, https, fs or a local path)"
+            )),
+        }
+    } else {
+        parse_dir(raw).map(|_| raw.to_string())
+    }
+}
+
+fn parse_dir(raw: &str) -> Result<PathBuf, String> {
+    if raw.contains('\0') {
+        return Err("path contains a NUL byte".into());
+    }
+    if raw.contains("://") {
+        return Err("expected a local path, not a URL".into());
+    }
+    Ok(PathBuf::from(raw.trim()))
+}
+
+/// Any non-zero [`parse_duration`] value.
+fn parse_interval(raw: &str) -> Result<Duration, String> {
+    match parse_duration(raw) {
+        Ok(Duration::ZERO) => Err("interval must be greater than zero".into()),
+        Ok(interval) => Ok(interval),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn config(vars: &[(&str, &str)]) -> Result<RunnerEnv, InvalidEnv> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        RunnerEnv::from_lookup(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn every_invalid_variable_is_reported() {
+        let cfg = config(&[
+            ("PORT", "9090"),
+            ("SECRETS_BACKEND", "AWS"),
+            ("PACK_SOURCE", "oci"),
+            ("PACK_INDEX_URL", "https://packs.example.com/index.json"),
+            ("PACK_REFRESH_INTERVAL", "1m30s"),
+            ("PACKS_STRICT", "yes"),
+            ("TENANT_RESOLVER", ""),
+        ])
+        .unwrap();
+        assert_eq!(cfg.port, 9090);
+        assert_eq!(cfg.secrets_backend, SecretsBackendKind::Aws);
+        assert_eq!(cfg.pack_source, PackSource::Oci);
+        assert_eq!(cfg.pack_refresh_interval, Duration::from_secs(90));
+        assert!(cfg.packs_strict);
+        assert_eq!(cfg.tenant_resolver, TenantResolver::Host);
+
+        let err = config(&[
+            ("PORT", "80800"),
+            ("SECRETS_BACKEND", "vault"),
+            ("PACK_SOURCE", "ftp"),
+            ("PACK_INDEX_URL", "ftp://packs/index.json"),
+            ("PACK_CACHE_DIR", "s3://bucket"),
+            ("PACK_PUBLIC_KEY", "not-a-key"),
+            ("PACK_REFRESH_INTERVAL", "soon"),
+            ("PACKS_STRICT", "maybe"),
+            ("TENANT_RESOLVER", "cookie"),
+            ("RUNNER_SECRET_GRANTS", "customera"),
+        ])
+        .unwrap_err();
+        let InvalidEnv(problems) = &err;
+        let vars: Vec<_> = problems.iter().map(|problem| problem.var).collect();
+        assert_eq!(
+            vars,
+            vec![
+                "PORT",
+                "SECRETS_BACKEND",
+                "PACK_SOURCE",
+                "PACK_INDEX_URL",
+                "PACK_CACHE_DIR",
+                "PACK_PUBLIC_KEY",
+                "PACK_REFRESH_INTERVAL",
+                "PACKS_STRICT",
+                "TENANT_RESOLVER",
+                "RUNNER_SECRET_GRANTS",
+            ]
+        );
+        assert!(
+            err.to_string()
+                .contains("SECRETS_BACKEND=\"vault\": expected one of env, aws, gcp, azure")
+        );
+    }
+}
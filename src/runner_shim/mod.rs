@@ -1,8 +1,14 @@
-//! Runner shim that either re-exports the upstream API or falls back to a stub.
+//! Runner shim that either re-exports the upstream API or falls back to a minimal local host.
+
+mod env;
+
+pub use env::{EnvProblem, InvalidEnv, PackSource, RunnerEnv, SecretsBackendKind, TenantResolver};
 
 #[cfg(feature = "use-runner-api")]
 pub use greentic_runner_host::{RunnerConfig, run};
 
+#[cfg(all(not(feature = "use-runner-api"), feature = "runner-shim"))]
+mod server;
 #[cfg(all(not(feature = "use-runner-api"), feature = "runner-shim"))]
 mod shim;
 
@@ -0,0 +1,380 @@
+//! Minimal local host behind the `runner-shim` fallback: JSON activities in, replies out.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::header::{AUTHORIZATION, HOST};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tokio::time::{self, MissedTickBehavior};
+
+use super::env::{PackSource, RunnerEnv, SecretsBackendKind, TenantResolver};
+use super::shim::RunnerConfig;
+use crate::config::Mode;
+use crate::health::HealthMonitor;
+use crate::runner_bridge::RunnerBridge;
+use crate::types::Activity;
+
+#[derive(Clone)]
+struct LocalRunner {
+    runner: RunnerBridge,
+    health: HealthMonitor,
+    resolver: TenantResolver,
+    default_tenant: String,
+}
+
+pub(super) async fn serve(cfg: RunnerConfig) -> Result<()> {
+    check_supported(&cfg.env)?;
+    let runner = RunnerBridge::new(Mode::Dev, cfg.env.secret_policy.clone());
+    let health = HealthMonitor::new(Duration::from_secs(60));
+
+    let mut registered = Vec::new();
+    for pack in &cfg.tenants {
+        match runner.register_pack(pack).await {
+            Ok(()) => {
+                health.record_pack(&pack.tenant, &pack.digest);
+                registered.push(pack.tenant.as_str());
+            }
+            Err(err) => {
+                tracing::error!(tenant = %pack.tenant, error = %format!("{err:#}"), "failed to register tenant; skipping");
+            }
+        }
+    }
+    if registered.is_empty() {
+        bail!("no tenant could be registered with the local runner");
+    }
+
+    let listener = TcpListener::bind(("0.0.0.0", cfg.env.port))
+        .await
+        .with_context(|| format!("failed to listen on port {}", cfg.env.port))?;
+    tracing::info!(
+        addr = %listener.local_addr()?,
+        tenants = ?registered,
+        resolver = ?cfg.env.tenant_resolver,
+        "local runner listening; enable use-runner-api for the full runner host"
+    );
+    let _reporter = health.spawn_reporter();
+    let refresher = spawn_refresh(cfg.env.clone(), runner.clone(), health.clone());
+    let state = LocalRunner {
+        runner,
+        health,
+        resolver: cfg.env.tenant_resolver,
+        default_tenant: cfg.env.default_tenant,
+    };
+    let served = axum::serve(listener, router(state))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+            tracing::info!("shutdown signal received; stopping local runner");
+        })
+        .await
+        .context("local runner server failed");
+    refresher.abort();
+    served
+}
+
+/// The local runner reads secrets through the demo's provider chain and loads packs from
+/// `PACKS_DIR` or `PACK_INDEX_URL`; other backends need the runner host.
+fn check_supported(env: &RunnerEnv) -> Result<()> {
+    if env.secrets_backend != SecretsBackendKind::Env {
+        bail!(
+            "SECRETS_BACKEND={} needs use-runner-api; the local runner only supports env",
+            format!("{:?}", env.secrets_backend).to_ascii_lowercase()
+        );
+    }
+    let http_index = env
+        .pack_index_url
+        .as_deref()
+        .is_some_and(|url| url.starts_with("http://") || url.starts_with("https://"));
+    match env.pack_source {
+        PackSource::Fs => Ok(()),
+        PackSource::Http if http_index => Ok(()),
+        PackSource::Http => bail!("PACK_SOURCE=http needs an http(s) PACK_INDEX_URL"),
+        other => bail!(
+            "PACK_SOURCE={} needs use-runner-api; the local runner only supports fs and http",
+            format!("{other:?}").to_ascii_lowercase()
+        ),
+    }
+}
+
+/// Re-discovers packs every `PACK_REFRESH_INTERVAL` and re-registers tenants whose digest changed.
+fn spawn_refresh(env: RunnerEnv, runner: RunnerBridge, health: HealthMonitor) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = time::interval(env.pack_refresh_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let tenants = match env.discover(false).await {
+                Ok(tenants) => tenants,
+                Err(err) => {
+                    tracing::warn!(error = %format!("{err:#}"), "pack refresh failed; keeping loaded tenants");
+                    continue;
+                }
+            };
+            for pack in &tenants {
+                let current = runner.pack_digest(&pack.tenant).await;
+                if current.as_deref() == Some(pack.digest.as_str()) {
+                    continue;
+                }
+                match runner.register_pack(pack).await {
+                    Ok(()) => {
+                        health.record_pack(&pack.tenant, &pack.digest);
+                        tracing::info!(tenant = %pack.tenant, digest = %pack.digest, "tenant pack reloaded");
+                    }
+                    Err(err) => {
+                        tracing::error!(tenant = %pack.tenant, error = %format!("{err:#}"), "failed to reload tenant; keeping the previous pack");
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn router(state: LocalRunner) -> Router {
+    Router::new()
+        .route("/healthz", get(|| async { "ok" }))
+        .route("/status", get(status))
+        .route("/activities", post(resolved_activity))
+        .route("/tenants/{tenant}/activities", post(tenant_activity))
+        .with_state(state)
+}
+
+async fn status(State(state): State<LocalRunner>) -> Response {
+    Json(state.health.status()).into_response()
+}
+
+async fn tenant_activity(
+    State(state): State<LocalRunner>,
+    Path(tenant): Path<String>,
+    activity: Result<Json<Activity>, JsonRejection>,
+) -> Response {
+    handle(state, tenant, activity).await
+}
+
+/// `POST /activities`: the tenant comes from `TENANT_RESOLVER`.
+async fn resolved_activity(
+    State(state): State<LocalRunner>,
+    headers: HeaderMap,
+    activity: Result<Json<Activity>, JsonRejection>,
+) -> Response {
+    let tenant =
+        resolve_tenant(state.resolver, &headers).unwrap_or_else(|| state.default_tenant.clone());
+    handle(state, tenant, activity).await
+}
+
+/// Same rules as the runner host: first `Host` label, `x-greentic-tenant`, or the bearer
+/// token's `tenant` claim.
+fn resolve_tenant(resolver: TenantResolver, headers: &HeaderMap) -> Option<String> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let tenant = match resolver {
+        TenantResolver::Env => return None,
+        TenantResolver::Host => header(HOST.as_str())?.split('.').next()?.to_string(),
+        TenantResolver::Header => header(TENANT_HEADER)?.to_string(),
+        TenantResolver::Jwt => {
+            let token = header(AUTHORIZATION.as_str())?.strip_prefix("Bearer ")?;
+            let claims = URL_SAFE_NO_PAD
+                .decode(token.split('.').nth(1)?.trim_end_matches('='))
+                .ok()?;
+            let claims: Value = serde_json::from_slice(&claims).ok()?;
+            claims.get("tenant")?.as_str()?.to_string()
+        }
+    };
+    Some(tenant).filter(|tenant| !tenant.is_empty())
+}
+
+async fn handle(
+    state: LocalRunner,
+    tenant: String,
+    activity: Result<Json<Activity>, JsonRejection>,
+) -> Response {
+    if state.runner.pack_digest(&tenant).await.is_none() {
+        return error(
+            StatusCode::NOT_FOUND,
+            format!("tenant {tenant} is not registered"),
+        );
+    }
+    let Json(activity) = match activity {
+        Ok(activity) => activity,
+        Err(rejection) => {
+            state.health.record_failure(&tenant);
+            return error(
+                StatusCode::BAD_REQUEST,
+                format!("invalid activity: {}", rejection.body_text()),
+            );
+        }
+    };
+
+    state.health.record_ingress(&tenant);
+    match state.runner.handle_activity(&tenant, activity).await {
+        Ok(responses) => {
+            for _ in &responses {
+                state.health.record_egress(&tenant);
+            }
+            Json(responses).into_response()
+        }
+        Err(err) => {
+            tracing::error!(tenant = %tenant, error = %format!("{err:#}"), "failed to process activity");
+            state.health.record_failure(&tenant);
+            error(StatusCode::INTERNAL_SERVER_ERROR, format!("{err:#}"))
+        }
+    }
+}
+
+const TENANT_HEADER: &str = "x-greentic-tenant";
+
+fn error(status: StatusCode, message: String) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::load_packs;
+    use crate::pack_signing::PackVerifier;
+    use crate::secret_policy::SecretPolicy;
+
+    async fn spawn(state: LocalRunner) -> (String, tokio::task::JoinHandle<std::io::Result<()>>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move { axum::serve(listener, router(state)).await });
+        (base, server)
+    }
+
+    fn local_runner(runner: RunnerBridge, health: HealthMonitor) -> LocalRunner {
+        LocalRunner {
+            runner,
+            health,
+            resolver: TenantResolver::Header,
+            default_tenant: "customerb".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_tenants_and_health_routes() {
+        let health = HealthMonitor::new(Duration::from_secs(60));
+        health.record_pack("customera", "sha256:abc");
+        let (base, server) = spawn(local_runner(
+            RunnerBridge::new(Mode::Dev, SecretPolicy::default()),
+            health,
+        ))
+        .await;
+
+        let client = reqwest::Client::new();
+        let healthz = client.get(format!("{base}/healthz")).send().await.unwrap();
+        assert_eq!(healthz.text().await.unwrap(), "ok");
+
+        let status: serde_json::Value = client
+            .get(format!("{base}/status"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(status[0]["pack_digest"], "sha256:abc");
+
+        let missing = client
+            .post(format!("{base}/tenants/customerz/activities"))
+            .json(&Activity::default())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+        let body: serde_json::Value = missing.json().await.unwrap();
+        assert_eq!(body["error"], "tenant customerz is not registered");
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn repo_tenants_reply_by_path_and_by_resolver() {
+        let dir = tempfile::tempdir().unwrap();
+        let packs = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("packs");
+        let report =
+            load_packs(&packs, &dir.path().join("cache"), &PackVerifier::default()).unwrap();
+        let runner = RunnerBridge::new(Mode::Dev, SecretPolicy::default());
+        for pack in &report.tenants {
+            runner.register_pack(pack).await.unwrap();
+        }
+        let (base, server) = spawn(local_runner(
+            runner,
+            HealthMonitor::new(Duration::from_secs(60)),
+        ))
+        .await;
+
+        let client = reqwest::Client::new();
+        let activity = Activity {
+            text: Some("hi".into()),
+            ..Activity::default()
+        };
+        let by_path = client
+            .post(format!("{base}/tenants/customera/activities"))
+            .json(&activity)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(by_path.status(), reqwest::StatusCode::OK);
+        let replies: Vec<Activity> = by_path.json().await.unwrap();
+        assert_eq!(replies[0].text.as_deref(), Some("Customer A received: hi"));
+
+        let by_header: Vec<Activity> = client
+            .post(format!("{base}/activities"))
+            .header(TENANT_HEADER, "customerc")
+            .json(&activity)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            by_header[0].text.as_deref(),
+            Some("Customer C received: hi")
+        );
+
+        let by_default: Vec<Activity> = client
+            .post(format!("{base}/activities"))
+            .json(&activity)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            by_default[0].text.as_deref(),
+            Some("Customer B received: hi")
+        );
+
+        server.abort();
+    }
+
+    #[test]
+    fn resolvers_follow_the_runner_host_rules() {
+        let mut headers = HeaderMap::new();
+        headers.insert(HOST, "customera.demo.local:8080".parse().unwrap());
+        headers.insert(TENANT_HEADER, "customerb".parse().unwrap());
+        let claims = URL_SAFE_NO_PAD.encode(r#"{"tenant":"customerc"}"#);
+        headers.insert(
+            AUTHORIZATION,
+            format!("Bearer e30.{claims}.sig").parse().unwrap(),
+        );
+        let resolve = |resolver| resolve_tenant(resolver, &headers);
+        assert_eq!(resolve(TenantResolver::Host).as_deref(), Some("customera"));
+        assert_eq!(
+            resolve(TenantResolver::Header).as_deref(),
+            Some("customerb")
+        );
+        assert_eq!(resolve(TenantResolver::Jwt).as_deref(), Some("customerc"));
+        assert_eq!(resolve(TenantResolver::Env), None);
+    }
+}
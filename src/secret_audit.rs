@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+
+/// Tracing target every [`SecretAccess`] is logged under; route it to its own sink with
+/// e.g. `RUST_LOG=greentic_demo::secret_audit=info`.
+pub const AUDIT_TARGET: &str = "greentic_demo::secret_audit";
+
+/// One secret lookup: who asked for what and where it came from. Never carries the value.
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretAccess {
+    pub at: DateTime<Utc>,
+    /// Name as requested, e.g. `NATS_SEED`.
+    pub secret: String,
+    /// Key that was served; `tenants/<tenant>/<name>` when a tenant-scoped copy exists.
+    pub key: String,
+    pub tenant: Option<String>,
+    /// Component that asked, e.g. `runner`, `bindings`, `nats`.
+    pub requester: String,
+    /// Provider that served the value; `None` unless the lookup succeeded.
+    pub provider: Option<String>,
+    /// Served from the chain's cache rather than the provider.
+    pub cached: bool,
+    pub outcome: AccessOutcome,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessOutcome {
+    Granted,
+    NotFound,
+    Failed,
+}
+
+impl AccessOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AccessOutcome::Granted => "granted",
+            AccessOutcome::NotFound => "not_found",
+            AccessOutcome::Failed => "failed",
+        }
+    }
+}
+
+/// Receives every [`SecretAccess`] inline on the reading thread; hand events off.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, event: &SecretAccess);
+}
+
+static SINKS: RwLock<Vec<Arc<dyn AuditSink>>> = RwLock::new(Vec::new());
+
+/// Registers `sink` for all subsequent secret reads.
+pub fn add_sink(sink: Arc<dyn AuditSink>) {
+    SINKS.write().push(sink);
+}
+
+pub(crate) fn record(event: SecretAccess) {
+    match event.outcome {
+        AccessOutcome::Granted => tracing::info!(
+            target: AUDIT_TARGET,
+            secret = %event.secret,
+            key = %event.key,
+            tenant = event.tenant.as_deref().unwrap_or("-"),
+            requester = %event.requester,
+            provider = event.provider.as_deref().unwrap_or("-"),
+            cached = event.cached,
+            outcome = event.outcome.as_str(),
+            "secret access"
+        ),
+        AccessOutcome::NotFound | AccessOutcome::Failed => tracing::warn!(
+            target: AUDIT_TARGET,
+            secret = %event.secret,
+            key = %event.key,
+            tenant = event.tenant.as_deref().unwrap_or("-"),
+            requester = %event.requester,
+            outcome = event.outcome.as_str(),
+            error = event.error.as_deref().unwrap_or("-"),
+            "secret access"
+        ),
+    }
+    for sink in SINKS.read().iter() {
+        sink.record(&event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::secrets::{SecretChain, SecretProvider};
+
+    struct Fixed;
+
+    impl SecretProvider for Fixed {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+            Ok((key == "tenants/audited/AUDIT_TEST_TOKEN").then(|| "s3cr3t-value".to_string()))
+        }
+    }
+
+    struct Collect(Mutex<Vec<SecretAccess>>);
+
+    impl AuditSink for Collect {
+        fn record(&self, event: &SecretAccess) {
+            if event.secret.starts_with("AUDIT_TEST_") {
+                self.0.lock().push(event.clone());
+            }
+        }
+    }
+
+    #[test]
+    fn reads_are_audited_without_values() {
+        let sink = Arc::new(Collect(Mutex::new(Vec::new())));
+        add_sink(sink.clone());
+        let chain = SecretChain::new()
+            .with_ttl(std::time::Duration::from_secs(60))
+            .with_provider(Fixed);
+
+        chain
+            .read_for_tenant_as("runner", "audited", "AUDIT_TEST_TOKEN")
+            .unwrap();
+        chain
+            .read_for_tenant_as("runner", "audited", "AUDIT_TEST_TOKEN")
+            .unwrap();
+        assert!(chain.read_as("nats", "AUDIT_TEST_MISSING").is_err());
+
+        let events = sink.0.lock().clone();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].outcome, AccessOutcome::Granted);
+        assert_eq!(events[0].key, "tenants/audited/AUDIT_TEST_TOKEN");
+        assert_eq!(events[0].tenant.as_deref(), Some("audited"));
+        assert_eq!(events[0].requester, "runner");
+        assert_eq!(events[0].provider.as_deref(), Some("fixed"));
+        assert!(!events[0].cached);
+        assert!(events[1].cached);
+        assert_eq!(events[2].outcome, AccessOutcome::NotFound);
+        assert_eq!(events[2].requester, "nats");
+        assert_eq!(events[2].provider, None);
+        for event in &events {
+            assert!(!serde_json::to_string(event).unwrap().contains("s3cr3t"));
+        }
+    }
+}
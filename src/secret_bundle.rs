@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_yaml_bw as serde_yaml;
+
+use crate::secrets::SecretProvider;
+
+/// Path of the encrypted bundle read by [`BundleProvider::from_env`].
+pub const BUNDLE_ENV: &str = "GREENTIC_SECRETS_BUNDLE";
+
+/// Base64 32-byte AES-256-GCM key for the bundle (e.g. `openssl rand -base64 32`).
+pub const BUNDLE_KEY_ENV: &str = "GREENTIC_SECRETS_KEY";
+
+const CIPHER: &str = "aes-256-gcm";
+const FORMAT_VERSION: u32 = 1;
+/// Binds the ciphertext to this format so it cannot be replayed as another AEAD payload.
+const AAD: &[u8] = b"greentic-secrets-bundle/v1";
+
+/// On-disk form of an encrypted secrets bundle (JSON, safe to commit).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedBundle {
+    pub version: u32,
+    pub cipher: String,
+    /// Base64 96-bit nonce, fresh for every seal.
+    pub nonce: String,
+    /// Base64 ciphertext followed by the GCM tag.
+    pub ciphertext: String,
+}
+
+impl SealedBundle {
+    /// Encrypts `secrets` under `key`.
+    pub fn seal(secrets: &BTreeMap<String, String>, key: &BundleKey) -> Result<Self> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("failed to generate a nonce"))?;
+        let mut in_out = serde_json::to_vec(secrets)?;
+        key.cipher()?
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(AAD),
+                &mut in_out,
+            )
+            .map_err(|_| anyhow!("failed to encrypt secrets bundle"))?;
+        Ok(Self {
+            version: FORMAT_VERSION,
+            cipher: CIPHER.into(),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(in_out),
+        })
+    }
+
+    /// Decrypts the bundle; a wrong key and a tampered file fail the same way.
+    pub fn open(&self, key: &BundleKey) -> Result<BTreeMap<String, String>> {
+        if self.version != FORMAT_VERSION || self.cipher != CIPHER {
+            bail!(
+                "unsupported secrets bundle (version {}, cipher {}); expected version {FORMAT_VERSION}, {CIPHER}",
+                self.version,
+                self.cipher
+            );
+        }
+        let nonce: [u8; NONCE_LEN] = BASE64
+            .decode(self.nonce.trim())
+            .context("bundle nonce is not base64")?
+            .try_into()
+            .map_err(|_| anyhow!("bundle nonce must be {NONCE_LEN} bytes"))?;
+        let mut in_out = BASE64
+            .decode(self.ciphertext.trim())
+            .context("bundle ciphertext is not base64")?;
+        let plaintext = key
+            .cipher()?
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(AAD),
+                &mut in_out,
+            )
+            .map_err(|_| {
+                anyhow!("failed to decrypt secrets bundle (wrong key or corrupted file)")
+            })?;
+        serde_json::from_slice(plaintext).context("decrypted bundle is not a JSON object")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read secrets bundle {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("secrets bundle {} is not valid JSON", path.display()))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, format!("{}\n", serde_json::to_string_pretty(self)?))
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// AES-256-GCM key for [`SealedBundle`]s.
+#[derive(Clone)]
+pub struct BundleKey([u8; 32]);
+
+impl fmt::Debug for BundleKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BundleKey([redacted])")
+    }
+}
+
+impl BundleKey {
+    /// Parses a base64-encoded 32-byte key.
+    pub fn from_base64(raw: &str) -> Result<Self> {
+        let bytes: [u8; 32] = BASE64
+            .decode(raw.trim())
+            .context("secrets bundle key is not base64")?
+            .try_into()
+            .map_err(|_| anyhow!("secrets bundle key must be 32 bytes"))?;
+        Ok(Self(bytes))
+    }
+
+    fn cipher(&self) -> Result<LessSafeKey> {
+        let key = UnboundKey::new(&AES_256_GCM, &self.0)
+            .map_err(|_| anyhow!("invalid secrets bundle key"))?;
+        Ok(LessSafeKey::new(key))
+    }
+}
+
+/// Serves secrets from an encrypted bundle decrypted once, in memory.
+pub struct BundleProvider {
+    path: PathBuf,
+    secrets: BTreeMap<String, String>,
+}
+
+impl fmt::Debug for BundleProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BundleProvider")
+            .field("path", &self.path)
+            .field("secrets", &self.secrets.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl BundleProvider {
+    pub fn open(path: &Path, key: &BundleKey) -> Result<Self> {
+        let secrets = SealedBundle::load(path)?
+            .open(key)
+            .with_context(|| format!("failed to open secrets bundle {}", path.display()))?;
+        tracing::info!(bundle = %path.display(), secrets = secrets.len(), "secrets bundle decrypted");
+        Ok(Self {
+            path: path.to_path_buf(),
+            secrets,
+        })
+    }
+
+    /// Provider for `GREENTIC_SECRETS_BUNDLE` keyed by `GREENTIC_SECRETS_KEY`; `None`
+    /// when no bundle is configured.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(path) = env::var(BUNDLE_ENV).ok().filter(|s| !s.trim().is_empty()) else {
+            return Ok(None);
+        };
+        let raw = env::var(BUNDLE_KEY_ENV)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .ok_or_else(|| anyhow!("{BUNDLE_ENV} is set but {BUNDLE_KEY_ENV} is not"))?;
+        let key =
+            BundleKey::from_base64(&raw).with_context(|| format!("invalid {BUNDLE_KEY_ENV}"))?;
+        Self::open(Path::new(&path), &key).map(Some)
+    }
+}
+
+impl SecretProvider for BundleProvider {
+    fn name(&self) -> &str {
+        "bundle"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.secrets.get(key).cloned())
+    }
+}
+
+/// Reads a plaintext YAML/JSON map of secret names to values for [`SealedBundle::seal`].
+pub fn read_plaintext(path: &Path) -> Result<BTreeMap<String, String>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let raw: BTreeMap<String, serde_yaml::Value> = serde_yaml::from_str(&contents)
+        .with_context(|| format!("{} must map secret names to values", path.display()))?;
+    // Values are converted by hand so no error message can echo a secret.
+    raw.into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                serde_yaml::Value::String(value, _) => value,
+                serde_yaml::Value::Number(value, _) => value.to_string(),
+                serde_yaml::Value::Bool(value, _) => value.to_string(),
+                _ => bail!("secret {name} in {} is not a scalar", path.display()),
+            };
+            Ok((name, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_bundles_round_trip_and_reject_wrong_keys() {
+        let key = BundleKey::from_base64(&BASE64.encode([7u8; 32])).unwrap();
+        let other = BundleKey::from_base64(&BASE64.encode([8u8; 32])).unwrap();
+        let secrets = BTreeMap::from([
+            ("NATS_JWT".to_string(), "jwt-value".to_string()),
+            (
+                "tenants/customera/TELEGRAM_BOT_TOKEN".to_string(),
+                "bot-a".to_string(),
+            ),
+        ]);
+
+        let sealed = SealedBundle::seal(&secrets, &key).unwrap();
+        assert!(!sealed.ciphertext.contains("jwt-value"));
+        assert_eq!(sealed.open(&key).unwrap(), secrets);
+        assert!(sealed.open(&other).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let path = root.join("secrets.bundle.json");
+        sealed.write(&path).unwrap();
+        let provider = BundleProvider::open(&path, &key).unwrap();
+        assert_eq!(
+            provider
+                .get("tenants/customera/TELEGRAM_BOT_TOKEN")
+                .unwrap()
+                .as_deref(),
+            Some("bot-a")
+        );
+        assert_eq!(provider.get("MISSING").unwrap(), None);
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Result, anyhow, bail};
+use greentic_runner_host::config::HostConfig;
+
+/// Which secrets each tenant's bindings may declare: the global allow-list, narrowed by
+/// an optional per-tenant grant.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SecretPolicy {
+    allowed: BTreeSet<String>,
+    grants: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SecretPolicy {
+    pub fn new<I, S>(allowed: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            allowed: allowed
+                .into_iter()
+                .map(Into::into)
+                .map(|secret: String| secret.trim().to_string())
+                .filter(|secret| !secret.is_empty())
+                .collect(),
+            grants: BTreeMap::new(),
+        }
+    }
+
+    /// Builds the policy from `--allowed-secrets` and `--secret-grant TENANT=A,B` values.
+    pub fn from_args(allowed: &[String], grants: &[String]) -> Result<Self> {
+        let mut policy = Self::new(allowed);
+        for raw in grants.iter().filter(|raw| !raw.trim().is_empty()) {
+            let (tenant, secrets) = raw.split_once('=').ok_or_else(|| {
+                anyhow!("secret grant `{raw}` must look like TENANT=SECRET[,SECRET...]")
+            })?;
+            policy = policy.with_grant(tenant.trim(), secrets.split(','))?;
+        }
+        Ok(policy)
+    }
+
+    /// Grants `tenant` exactly `secrets`, which must all be on the allow-list (if any).
+    pub fn with_grant<I, S>(mut self, tenant: &str, secrets: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        if tenant.is_empty() {
+            bail!("secret grant is missing a tenant name");
+        }
+        let secrets: BTreeSet<String> = secrets
+            .into_iter()
+            .map(Into::into)
+            .map(|secret: String| secret.trim().to_string())
+            .filter(|secret| !secret.is_empty())
+            .collect();
+        if !self.allowed.is_empty() {
+            let outside: Vec<&str> = secrets
+                .iter()
+                .filter(|secret| !self.allowed.contains(*secret))
+                .map(String::as_str)
+                .collect();
+            if !outside.is_empty() {
+                bail!(
+                    "secret grant for tenant {tenant} names secrets outside the allow-list: {}",
+                    outside.join(", ")
+                );
+            }
+        }
+        if self.grants.insert(tenant.to_string(), secrets).is_some() {
+            bail!("tenant {tenant} has more than one secret grant");
+        }
+        Ok(self)
+    }
+
+    pub fn allowed(&self) -> &BTreeSet<String> {
+        &self.allowed
+    }
+
+    pub fn grants(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.grants
+    }
+
+    /// Secrets `tenant` may declare; `None` when it is unrestricted.
+    pub fn granted(&self, tenant: &str) -> Option<&BTreeSet<String>> {
+        match self.grants.get(tenant) {
+            Some(grant) => Some(grant),
+            None if self.allowed.is_empty() => None,
+            None => Some(&self.allowed),
+        }
+    }
+
+    /// Requires the `secrets` of every flow binding in `config` to be granted to its tenant.
+    pub fn check(&self, config: &HostConfig) -> Result<()> {
+        self.check_bindings(
+            &config.tenant,
+            config
+                .flow_type_bindings
+                .iter()
+                .map(|(flow_type, binding)| (flow_type.as_str(), binding.secrets.as_slice())),
+        )
+    }
+
+    fn check_bindings<'a>(
+        &self,
+        tenant: &str,
+        bindings: impl IntoIterator<Item = (&'a str, &'a [String])>,
+    ) -> Result<()> {
+        let Some(granted) = self.granted(tenant) else {
+            return Ok(());
+        };
+        let mut denied: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for (flow_type, secrets) in bindings {
+            for secret in secrets {
+                if !granted.contains(secret) {
+                    denied.entry(flow_type).or_default().insert(secret);
+                }
+            }
+        }
+        if denied.is_empty() {
+            return Ok(());
+        }
+        let denied = denied
+            .iter()
+            .map(|(flow_type, secrets)| {
+                format!(
+                    "flow_type_bindings.{flow_type}: {}",
+                    secrets.iter().copied().collect::<Vec<_>>().join(", ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        let granted = if granted.is_empty() {
+            "none".to_string()
+        } else {
+            granted.iter().cloned().collect::<Vec<_>>().join(", ")
+        };
+        bail!("tenant {tenant} declares secrets it is not granted ({denied}); granted: {granted}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn bindings_must_be_a_subset_of_the_tenant_grant() {
+        let policy = SecretPolicy::from_args(
+            &secrets(&["TELEGRAM_BOT_TOKEN", "OPENAI_KEY", "WEBHOOK_KEY"]),
+            &secrets(&["customerb=TELEGRAM_BOT_TOKEN"]),
+        )
+        .unwrap();
+
+        let fewer = secrets(&["TELEGRAM_BOT_TOKEN"]);
+        assert!(
+            policy
+                .check_bindings("customera", [("messaging", fewer.as_slice())])
+                .is_ok()
+        );
+
+        let extra = secrets(&["TELEGRAM_BOT_TOKEN", "OPENAI_KEY", "STRIPE_KEY"]);
+        let err = policy
+            .check_bindings("customerb", [("messaging", extra.as_slice())])
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "tenant customerb declares secrets it is not granted \
+             (flow_type_bindings.messaging: OPENAI_KEY, STRIPE_KEY); granted: TELEGRAM_BOT_TOKEN"
+        );
+
+        assert!(SecretPolicy::default().granted("customera").is_none());
+    }
+
+    #[test]
+    fn grants_cannot_exceed_the_allow_list() {
+        let err = SecretPolicy::from_args(
+            &secrets(&["TELEGRAM_BOT_TOKEN"]),
+            &secrets(&["customera=TELEGRAM_BOT_TOKEN,STRIPE_KEY"]),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("outside the allow-list: STRIPE_KEY")
+        );
+        assert!(SecretPolicy::from_args(&[], &secrets(&["customera"])).is_err());
+    }
+}
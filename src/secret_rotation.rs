@@ -0,0 +1,281 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::duration::env_duration;
+use crate::secrets::{self, SecretChain};
+
+/// Seconds between [`SecretWatcher`] polls when spawned via [`SecretWatcher::from_env`].
+pub const WATCH_INTERVAL_ENV: &str = "GREENTIC_SECRETS_WATCH_INTERVAL_SECS";
+
+const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Current values of the keys a rotation callback subscribed to; unresolvable keys are
+/// left out.
+pub type RotatedSecrets = BTreeMap<String, String>;
+
+type RotationCallback = Arc<dyn Fn(&RotatedSecrets) + Send + Sync>;
+
+/// SHA-256 of each subscribed key's current value, so polls compare without reading.
+type SecretDigests = BTreeMap<String, [u8; 32]>;
+
+struct Subscription {
+    keys: Vec<String>,
+    digests: SecretDigests,
+    callback: RotationCallback,
+}
+
+/// Polls for rotated secrets and calls each subscription with all its keys when any changed.
+pub struct SecretWatcher {
+    chain: Arc<SecretChain>,
+    dir: Option<PathBuf>,
+    files: BTreeMap<String, SystemTime>,
+    subscriptions: Vec<Subscription>,
+    interval: Duration,
+}
+
+impl SecretWatcher {
+    pub fn new(chain: Arc<SecretChain>) -> Self {
+        Self {
+            chain,
+            dir: None,
+            files: BTreeMap::new(),
+            subscriptions: Vec::new(),
+            interval: DEFAULT_WATCH_INTERVAL,
+        }
+    }
+
+    /// Watches the installed chain and `GREENTIC_SECRETS_DIR`, polling every
+    /// `GREENTIC_SECRETS_WATCH_INTERVAL_SECS` (default 30).
+    pub fn from_env() -> Result<Self> {
+        let mut watcher = Self::new(secrets::global()?);
+        if let Some(interval) = env_duration(WATCH_INTERVAL_ENV)? {
+            watcher.interval = interval.max(Duration::from_secs(1));
+        }
+        if let Some(dir) = env::var("GREENTIC_SECRETS_DIR")
+            .ok()
+            .filter(|s| !s.is_empty())
+        {
+            watcher = watcher.watch_dir(dir);
+        }
+        Ok(watcher)
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Treats files under `dir` as secrets named by their relative path.
+    pub fn watch_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        self.files = scan_dir(&dir);
+        self.dir = Some(dir);
+        self
+    }
+
+    /// Calls `callback` whenever one of `keys` resolves to a new value.
+    pub fn on_rotate<F>(&mut self, keys: &[&str], callback: F)
+    where
+        F: Fn(&RotatedSecrets) + Send + Sync + 'static,
+    {
+        let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+        let digests = self.digests(&keys);
+        self.subscriptions.push(Subscription {
+            keys,
+            digests,
+            callback: Arc::new(callback),
+        });
+    }
+
+    /// Runs one check and returns how many subscriptions were notified. Keys are only read
+    /// (and audited) for subscriptions whose digests changed.
+    pub fn poll(&mut self) -> usize {
+        if let Some(dir) = &self.dir {
+            let files = scan_dir(dir);
+            for (key, modified) in &files {
+                if self.files.get(key) != Some(modified) {
+                    self.chain.invalidate(key);
+                }
+            }
+            for key in self.files.keys() {
+                if !files.contains_key(key) {
+                    self.chain.invalidate(key);
+                }
+            }
+            self.files = files;
+        }
+
+        let mut notified = 0;
+        for index in 0..self.subscriptions.len() {
+            let digests = self.digests(&self.subscriptions[index].keys);
+            if digests == self.subscriptions[index].digests {
+                continue;
+            }
+            let current = self.current(&self.subscriptions[index].keys);
+            let subscription = &mut self.subscriptions[index];
+            let changed: Vec<&str> = subscription
+                .keys
+                .iter()
+                .filter(|key| digests.get(*key) != subscription.digests.get(*key))
+                .map(String::as_str)
+                .collect();
+            tracing::info!(secrets = ?changed, "secrets rotated");
+            (subscription.callback)(&current);
+            subscription.digests = digests;
+            notified += 1;
+        }
+        notified
+    }
+
+    /// Polls on a blocking thread every interval until the returned task is aborted.
+    pub fn spawn(self) -> JoinHandle<()> {
+        let interval = self.interval;
+        let watcher = Arc::new(Mutex::new(self));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let watcher = Arc::clone(&watcher);
+                if let Err(err) = tokio::task::spawn_blocking(move || watcher.lock().poll()).await {
+                    tracing::error!(error = %err, "secret watcher poll failed");
+                }
+            }
+        })
+    }
+
+    fn digests(&self, keys: &[String]) -> SecretDigests {
+        keys.iter()
+            .filter_map(|key| match self.chain.digest(key) {
+                Ok(digest) => digest.map(|digest| (key.clone(), digest)),
+                Err(err) => {
+                    tracing::debug!(secret = %key, error = %format!("{err:#}"), "watched secret unavailable");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn current(&self, keys: &[String]) -> RotatedSecrets {
+        keys.iter()
+            .filter_map(|key| match self.chain.read_as("secret_watcher", key) {
+                Ok(value) => Some((key.clone(), value)),
+                Err(err) => {
+                    tracing::debug!(secret = %key, error = %format!("{err:#}"), "watched secret unavailable");
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Modification times of every file under `dir`, keyed by `/`-separated relative path.
+fn scan_dir(dir: &Path) -> BTreeMap<String, SystemTime> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let key = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let modified = entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            files.insert(key, modified);
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::secret_audit::{self, AuditSink, SecretAccess};
+    use crate::secrets::DirProvider;
+
+    /// Counts the reads the watcher itself performs.
+    #[derive(Default)]
+    struct WatcherReads(AtomicUsize);
+
+    impl AuditSink for WatcherReads {
+        fn record(&self, event: &SecretAccess) {
+            if event.requester == "secret_watcher" {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    #[test]
+    fn dir_changes_invalidate_cache_and_notify_subscribers() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("NATS_JWT"), "jwt-1").unwrap();
+        fs::write(root.join("NATS_SEED"), "seed-1").unwrap();
+        let chain = Arc::new(
+            SecretChain::new()
+                .with_ttl(Duration::from_secs(3600))
+                .with_provider(DirProvider::new(root)),
+        );
+
+        let reads = Arc::new(WatcherReads::default());
+        secret_audit::add_sink(reads.clone());
+
+        let mut watcher = SecretWatcher::new(Arc::clone(&chain)).watch_dir(root);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let calls = Arc::new(AtomicUsize::new(0));
+        {
+            let seen = Arc::clone(&seen);
+            let calls = Arc::clone(&calls);
+            watcher.on_rotate(&["NATS_JWT", "NATS_SEED"], move |values| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                seen.lock().push(values.clone());
+            });
+        }
+        assert_eq!(watcher.poll(), 0);
+        assert_eq!(reads.0.load(Ordering::SeqCst), 0);
+
+        // Make sure the new mtime differs even on coarse-grained filesystems.
+        let later = SystemTime::now() + Duration::from_secs(5);
+        fs::write(root.join("NATS_JWT"), "jwt-2").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(root.join("NATS_JWT"))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        assert_eq!(watcher.poll(), 1);
+        assert_eq!(watcher.poll(), 0);
+        assert_eq!(reads.0.load(Ordering::SeqCst), 2);
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let seen = seen.lock();
+        assert_eq!(seen[0]["NATS_JWT"], "jwt-2");
+        assert_eq!(seen[0]["NATS_SEED"], "seed-1");
+        assert_eq!(chain.read("NATS_JWT").unwrap(), "jwt-2");
+    }
+}
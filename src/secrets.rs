@@ -1,68 +1,826 @@
+use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
 
-/// Resolve secrets either from environment, greentic-secrets CLI, or files.
-pub fn read(name: &str) -> Result<String> {
-    if let Ok(value) = env::var(name)
-        && !value.trim().is_empty()
-    {
-        return Ok(value);
+use crate::duration::env_duration;
+use crate::secret_audit::{self, AccessOutcome, SecretAccess};
+use crate::secret_bundle::BundleProvider;
+
+/// Comma-separated provider order used by [`SecretChain::from_env`].
+pub const PROVIDERS_ENV: &str = "GREENTIC_SECRETS_PROVIDERS";
+
+/// Seconds a resolved secret is served from cache by [`SecretChain::from_env`]; `0` disables.
+pub const TTL_ENV: &str = "GREENTIC_SECRETS_TTL_SECS";
+
+const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+const DEFAULT_PROVIDERS: &[&str] = &["env", "bundle", "dir", "cli"];
+
+/// A source of secrets consulted by [`SecretChain`]; keys may be `tenants/<tenant>/NAME`.
+pub trait SecretProvider: Send + Sync {
+    /// Short name used in logs and in `GREENTIC_SECRETS_PROVIDERS`.
+    fn name(&self) -> &str;
+
+    /// Returns `Ok(None)` when this provider does not hold `key`, so the chain moves on.
+    fn get(&self, key: &str) -> Result<Option<String>>;
+
+    /// Like [`SecretProvider::get`], with version and expiry when the provider knows them.
+    fn get_entry(&self, key: &str) -> Result<Option<SecretValue>> {
+        Ok(self.get(key)?.map(SecretValue::new))
     }
+}
 
-    if let Some(value) = read_from_dir(name)? {
-        return Ok(value);
+/// Reads `key` from the process environment; `/`, `-` and `.` become `_` and the name is
+/// upper-cased, so `tenants/customera/TOKEN` is `TENANTS_CUSTOMERA_TOKEN`.
+#[derive(Debug, Default, Clone)]
+pub struct EnvProvider;
+
+impl SecretProvider for EnvProvider {
+    fn name(&self) -> &str {
+        "env"
     }
 
-    if let Some(value) = read_via_cli(name)? {
-        return Ok(value);
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(env::var(env_key(key))
+            .ok()
+            .filter(|value| !value.trim().is_empty()))
     }
+}
 
-    Err(anyhow!(
-        "secret {name} not found; set env, GREENTIC_SECRETS_DIR/{name}, or provide greentic-secrets CLI"
-    ))
+fn env_key(key: &str) -> String {
+    key.chars()
+        .map(|ch| match ch {
+            '/' | '-' | '.' => '_',
+            ch => ch.to_ascii_uppercase(),
+        })
+        .collect()
 }
 
-fn read_from_dir(name: &str) -> Result<Option<String>> {
-    let dir = match env::var("GREENTIC_SECRETS_DIR")
-        .ok()
-        .filter(|s| !s.is_empty())
-    {
-        Some(dir) => dir,
-        None => return Ok(None),
-    };
+/// Reads `<dir>/<key>` files (e.g. mounted Kubernetes secrets), trimming whitespace.
+#[derive(Debug, Clone)]
+pub struct DirProvider {
+    dir: PathBuf,
+}
+
+impl DirProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Provider for `GREENTIC_SECRETS_DIR`, if set.
+    pub fn from_env() -> Option<Self> {
+        env::var("GREENTIC_SECRETS_DIR")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(Self::new)
+    }
+}
+
+impl SecretProvider for DirProvider {
+    fn name(&self) -> &str {
+        "dir"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        let relative = Path::new(key);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            bail!("secret key {key:?} is not a relative path");
+        }
+        let path = self.dir.join(relative);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed reading secret file {path:?}"))?;
+        Ok(Some(contents.trim().to_string()))
+    }
+}
+
+/// Default for `GREENTIC_SECRETS_CLI_TIMEOUT_SECS`.
+const DEFAULT_CLI_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest stderr excerpt kept in [`CliError::Failed`].
+const MAX_STDERR: usize = 512;
+
+/// How [`CliProvider`] asks for and parses the CLI's output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CliFormat {
+    /// `read <key>` prints the bare value.
+    #[default]
+    Text,
+    /// `read <key> --format json` prints `{"value": ..., "version": ..., "expires_at": ...}`.
+    Json,
+}
+
+#[derive(Debug, Deserialize)]
+struct CliJsonOutput {
+    value: String,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Why the secrets CLI produced no value. Messages never include the secret or raw stderr.
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("{program} is not installed or not on PATH")]
+    NotInstalled { program: String },
+    #[error("failed to run {program}: {reason}")]
+    Spawn { program: String, reason: String },
+    #[error("{program} read {key} timed out after {timeout:?}")]
+    TimedOut {
+        program: String,
+        key: String,
+        timeout: Duration,
+    },
+    #[error("{program} read {key} failed ({status}): {stderr}")]
+    Failed {
+        program: String,
+        key: String,
+        status: String,
+        stderr: String,
+    },
+    #[error("{program} read {key} returned invalid output: {reason}")]
+    InvalidOutput {
+        program: String,
+        key: String,
+        reason: String,
+    },
+}
+
+/// Runs `<program> read <key>` with a timeout; a missing default program holds nothing.
+#[derive(Debug, Clone)]
+pub struct CliProvider {
+    program: String,
+    explicit: bool,
+    timeout: Duration,
+    format: CliFormat,
+}
+
+impl CliProvider {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            explicit: true,
+            timeout: DEFAULT_CLI_TIMEOUT,
+            format: CliFormat::Text,
+        }
+    }
+
+    /// Provider for `GREENTIC_SECRETS_CLI` (default `greentic-secrets`), with
+    /// `GREENTIC_SECRETS_CLI_TIMEOUT_SECS` and `GREENTIC_SECRETS_CLI_FORMAT` (`text`/`json`).
+    pub fn from_env() -> Result<Self> {
+        let configured = env::var("GREENTIC_SECRETS_CLI")
+            .ok()
+            .filter(|s| !s.trim().is_empty());
+        let mut provider = Self::new(
+            configured
+                .clone()
+                .unwrap_or_else(|| "greentic-secrets".into()),
+        );
+        provider.explicit = configured.is_some();
+        if let Some(timeout) = env_duration("GREENTIC_SECRETS_CLI_TIMEOUT_SECS")? {
+            provider.timeout = timeout.max(Duration::from_secs(1));
+        }
+        provider.format = match env::var("GREENTIC_SECRETS_CLI_FORMAT")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "" | "text" => CliFormat::Text,
+            "json" => CliFormat::Json,
+            other => bail!("unknown GREENTIC_SECRETS_CLI_FORMAT `{other}`; expected text or json"),
+        };
+        Ok(provider)
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_format(mut self, format: CliFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Runs the CLI for `key`, returning the value with any metadata it reported.
+    pub fn read(&self, key: &str) -> Result<SecretValue, CliError> {
+        let mut command = Command::new(&self.program);
+        command.arg("read").arg(key);
+        if self.format == CliFormat::Json {
+            command.args(["--format", "json"]);
+        }
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => CliError::NotInstalled {
+                    program: self.program.clone(),
+                },
+                _ => CliError::Spawn {
+                    program: self.program.clone(),
+                    reason: err.to_string(),
+                },
+            })?;
+
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(CliError::TimedOut {
+                        program: self.program.clone(),
+                        key: key.to_string(),
+                        timeout: self.timeout,
+                    });
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+                Err(err) => {
+                    let _ = child.kill();
+                    return Err(CliError::Spawn {
+                        program: self.program.clone(),
+                        reason: err.to_string(),
+                    });
+                }
+            }
+        };
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        if !status.success() {
+            return Err(CliError::Failed {
+                program: self.program.clone(),
+                key: key.to_string(),
+                status: status.to_string(),
+                stderr: redact(&String::from_utf8_lossy(&stderr)),
+            });
+        }
+
+        let invalid = |reason: String| CliError::InvalidOutput {
+            program: self.program.clone(),
+            key: key.to_string(),
+            reason,
+        };
+        let stdout = String::from_utf8(stdout).map_err(|_| invalid("not UTF-8".into()))?;
+        match self.format {
+            CliFormat::Text => Ok(SecretValue::new(stdout.trim())),
+            CliFormat::Json => {
+                // serde errors can quote the input, so only the position is reported.
+                let parsed: CliJsonOutput = serde_json::from_str(&stdout).map_err(|err| {
+                    invalid(format!(
+                        "expected JSON with a `value` field (line {}, column {})",
+                        err.line(),
+                        err.column()
+                    ))
+                })?;
+                Ok(SecretValue {
+                    value: parsed.value,
+                    version: parsed.version,
+                    expires_at: parsed.expires_at,
+                })
+            }
+        }
+    }
+}
+
+impl SecretProvider for CliProvider {
+    fn name(&self) -> &str {
+        "cli"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.get_entry(key)?.map(|entry| entry.value))
+    }
+
+    fn get_entry(&self, key: &str) -> Result<Option<SecretValue>> {
+        match self.read(key) {
+            Ok(value) => Ok(Some(value)),
+            Err(CliError::NotInstalled { program }) if !self.explicit => {
+                tracing::debug!(program = %program, "secrets CLI not installed; skipping");
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Shortens CLI stderr and masks anything that looks like a credential: values after
+/// `secret`/`token`/`password`/`key`-like labels and long opaque tokens.
+fn redact(stderr: &str) -> String {
+    const MASK: &str = "[redacted]";
+    let mut out = Vec::new();
+    let mut mask_next = false;
+    for word in stderr.split_whitespace() {
+        if mask_next {
+            out.push(MASK.to_string());
+            mask_next = false;
+            continue;
+        }
+        let lower = word.to_ascii_lowercase();
+        let sensitive = [
+            "secret", "token", "password", "passwd", "seed", "jwt", "key",
+        ]
+        .iter()
+        .any(|label| lower.contains(label));
+        if sensitive && let Some((label, value)) = word.split_once(['=', ':']) {
+            out.push(if value.is_empty() {
+                mask_next = true;
+                word.to_string()
+            } else {
+                format!("{label}={MASK}")
+            });
+            continue;
+        }
+        let opaque = word.len() >= 16
+            && word
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || "+/=_-.".contains(ch));
+        out.push(if opaque {
+            MASK.to_string()
+        } else {
+            word.to_string()
+        });
+    }
+    let mut joined = out.join(" ");
+    if joined.len() > MAX_STDERR {
+        let mut end = MAX_STDERR;
+        while !joined.is_char_boundary(end) {
+            end -= 1;
+        }
+        joined.truncate(end);
+        joined.push('…');
+    }
+    joined
+}
+
+/// A resolved secret and what its provider knows about it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretValue {
+    pub value: String,
+    pub version: Option<String>,
+    /// After this instant the value is not cached and is logged as expired.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl SecretValue {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            version: None,
+            expires_at: None,
+        }
+    }
+}
+
+impl fmt::Debug for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretValue")
+            .field("value", &"[redacted]")
+            .field("version", &self.version)
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+/// Ordered list of [`SecretProvider`]s; the first hit wins and is cached for `ttl`.
+#[derive(Clone, Default)]
+pub struct SecretChain {
+    providers: Vec<Arc<dyn SecretProvider>>,
+    ttl: Duration,
+    cache: Arc<Mutex<HashMap<String, CachedSecret>>>,
+}
+
+#[derive(Clone)]
+struct CachedSecret {
+    value: String,
+    provider: String,
+    expires: Instant,
+}
 
-    let mut path = PathBuf::from(dir);
-    path.push(name);
-    if !path.exists() {
-        return Ok(None);
+impl fmt::Debug for SecretChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretChain")
+            .field("providers", &self.providers().collect::<Vec<_>>())
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+impl SecretChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the chain named by `GREENTIC_SECRETS_PROVIDERS` (default `env,bundle,dir,cli`).
+    pub fn from_env() -> Result<Self> {
+        let order = env::var(PROVIDERS_ENV)
+            .ok()
+            .filter(|s| !s.trim().is_empty());
+        let names: Vec<String> = match &order {
+            Some(order) => order
+                .split(',')
+                .map(|name| name.trim().to_ascii_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            None => DEFAULT_PROVIDERS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        };
+
+        let ttl = env_duration(TTL_ENV)?.unwrap_or(DEFAULT_TTL);
+
+        let mut chain = Self::new().with_ttl(ttl);
+        for name in names {
+            match name.as_str() {
+                "env" => chain.register(EnvProvider),
+                "bundle" => {
+                    if let Some(provider) = BundleProvider::from_env()? {
+                        chain.register(provider);
+                    }
+                }
+                "dir" => {
+                    if let Some(provider) = DirProvider::from_env() {
+                        chain.register(provider);
+                    }
+                }
+                "cli" => chain.register(CliProvider::from_env()?),
+                other => bail!(
+                    "unknown secret provider `{other}` in {PROVIDERS_ENV}; expected env, bundle, dir or cli"
+                ),
+            }
+        }
+        Ok(chain)
+    }
+
+    /// Appends `provider`; it is consulted after every provider already registered.
+    pub fn register(&mut self, provider: impl SecretProvider + 'static) {
+        self.providers.push(Arc::new(provider));
+    }
+
+    pub fn with_provider(mut self, provider: impl SecretProvider + 'static) -> Self {
+        self.register(provider);
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &str> {
+        self.providers.iter().map(|provider| provider.name())
+    }
+
+    /// Drops the cached value of `key`; the next read goes back to the providers.
+    pub fn invalidate(&self, key: &str) {
+        self.cache.lock().remove(key);
+    }
+
+    pub fn invalidate_all(&self) {
+        self.cache.lock().clear();
+    }
+
+    /// Resolves `key` through the cache, then the providers in order, audited as `bridge`.
+    pub fn read(&self, key: &str) -> Result<String> {
+        self.read_as("bridge", key)
+    }
+
+    /// Like [`SecretChain::read`], recording `requester` in the audit event.
+    pub fn read_as(&self, requester: &str, key: &str) -> Result<String> {
+        let result = self.lookup(key);
+        audit(requester, None, key, key, &result);
+        self.found(key, result)
+    }
+
+    /// Resolves `tenants/<tenant>/<name>` first, falling back to the shared `name` only when
+    /// no provider has the scoped key; a failing provider is an error, not a fallback.
+    pub fn read_for_tenant(&self, tenant: &str, name: &str) -> Result<String> {
+        self.read_for_tenant_as("bridge", tenant, name)
+    }
+
+    /// Like [`SecretChain::read_for_tenant`], recording `requester` in the audit event.
+    pub fn read_for_tenant_as(&self, requester: &str, tenant: &str, name: &str) -> Result<String> {
+        let scoped = tenant_key(tenant, name);
+        let (key, result) = match self.lookup(&scoped) {
+            Ok(None) => (name.to_string(), self.lookup(name)),
+            result => (scoped, result),
+        };
+        audit(requester, Some(tenant), name, &key, &result);
+        self.found(&key, result)
     }
 
-    let contents = fs::read_to_string(&path)
-        .with_context(|| format!("failed reading secret file {path:?}"))?;
-    Ok(Some(contents.trim().to_string()))
+    /// SHA-256 of the value `key` currently resolves to, without an audit event; lets the
+    /// rotation watcher notice changes without logging a read on every poll.
+    pub(crate) fn digest(&self, key: &str) -> Result<Option<[u8; 32]>> {
+        Ok(self
+            .lookup(key)?
+            .map(|hit| Sha256::digest(hit.value.as_bytes()).into()))
+    }
+
+    fn lookup(&self, key: &str) -> Result<Option<Lookup>> {
+        if let Some(cached) = self.cache.lock().get(key)
+            && Instant::now() < cached.expires
+        {
+            return Ok(Some(Lookup {
+                value: cached.value.clone(),
+                provider: cached.provider.clone(),
+                cached: true,
+            }));
+        }
+
+        for provider in &self.providers {
+            let entry = provider
+                .get_entry(key)
+                .with_context(|| format!("secret provider {} failed", provider.name()))?;
+            let Some(entry) = entry else {
+                continue;
+            };
+            tracing::debug!(secret = %key, provider = provider.name(), version = ?entry.version, "secret resolved");
+            // Cache until the TTL or the provider-reported expiry, whichever comes first.
+            let mut lifetime = self.ttl;
+            if let Some(expires_at) = entry.expires_at {
+                let remaining = (expires_at - Utc::now()).to_std().unwrap_or_default();
+                if remaining.is_zero() {
+                    tracing::warn!(secret = %key, provider = provider.name(), %expires_at, "secret has expired");
+                }
+                lifetime = lifetime.min(remaining);
+            }
+            if !lifetime.is_zero() {
+                self.cache.lock().insert(
+                    key.to_string(),
+                    CachedSecret {
+                        value: entry.value.clone(),
+                        provider: provider.name().to_string(),
+                        expires: Instant::now() + lifetime,
+                    },
+                );
+            }
+            return Ok(Some(Lookup {
+                value: entry.value,
+                provider: provider.name().to_string(),
+                cached: false,
+            }));
+        }
+        self.invalidate(key);
+        Ok(None)
+    }
+
+    fn found(&self, key: &str, result: Result<Option<Lookup>>) -> Result<String> {
+        match result? {
+            Some(hit) => Ok(hit.value),
+            None => Err(anyhow!(
+                "secret {key} not found; providers tried: {}",
+                self.providers().collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
 }
 
-fn read_via_cli(name: &str) -> Result<Option<String>> {
-    let cli = env::var("GREENTIC_SECRETS_CLI").unwrap_or_else(|_| "greentic-secrets".into());
-    let output = Command::new(cli).arg("read").arg(name).output();
+/// A value resolved by [`SecretChain::lookup`] and where it came from.
+struct Lookup {
+    value: String,
+    provider: String,
+    cached: bool,
+}
 
-    let output = match output {
-        Ok(output) => output,
-        Err(_) => return Ok(None),
+fn audit(
+    requester: &str,
+    tenant: Option<&str>,
+    secret: &str,
+    key: &str,
+    result: &Result<Option<Lookup>>,
+) {
+    let (outcome, provider, cached, error) = match result {
+        Ok(Some(hit)) => (
+            AccessOutcome::Granted,
+            Some(hit.provider.clone()),
+            hit.cached,
+            None,
+        ),
+        Ok(None) => (AccessOutcome::NotFound, None, false, None),
+        Err(err) => (AccessOutcome::Failed, None, false, Some(format!("{err:#}"))),
     };
+    secret_audit::record(SecretAccess {
+        at: Utc::now(),
+        secret: secret.to_string(),
+        key: key.to_string(),
+        tenant: tenant.map(str::to_string),
+        requester: requester.to_string(),
+        provider,
+        cached,
+        outcome,
+        error,
+    });
+}
+
+/// Namespaced key of a tenant's own copy of `name`.
+pub fn tenant_key(tenant: &str, name: &str) -> String {
+    format!("tenants/{tenant}/{name}")
+}
+
+static GLOBAL: RwLock<Option<Arc<SecretChain>>> = RwLock::new(None);
+
+/// Replaces the chain used by [`read`] and [`read_for_tenant`], e.g. to add custom
+/// providers when embedding the bridge.
+pub fn install(chain: SecretChain) {
+    tracing::info!(providers = ?chain, "secret providers installed");
+    *GLOBAL.write() = Some(Arc::new(chain));
+}
+
+/// The installed chain, built from the environment on first use.
+pub fn global() -> Result<Arc<SecretChain>> {
+    if let Some(chain) = GLOBAL.read().as_ref() {
+        return Ok(chain.clone());
+    }
+    let mut slot = GLOBAL.write();
+    if let Some(chain) = slot.as_ref() {
+        return Ok(chain.clone());
+    }
+    let chain = Arc::new(SecretChain::from_env()?);
+    *slot = Some(chain.clone());
+    Ok(chain)
+}
+
+/// Resolve a secret through the installed chain (env, `GREENTIC_SECRETS_DIR`, then the
+/// greentic-secrets CLI unless configured otherwise).
+pub fn read(name: &str) -> Result<String> {
+    global()?.read(name)
+}
+
+/// Like [`read`], naming the component asking for the secret in the audit event.
+pub fn read_as(requester: &str, name: &str) -> Result<String> {
+    global()?.read_as(requester, name)
+}
+
+/// Resolve a tenant's secret, preferring `tenants/<tenant>/<name>` over `name`.
+pub fn read_for_tenant(tenant: &str, name: &str) -> Result<String> {
+    global()?.read_for_tenant(tenant, name)
+}
+
+/// Like [`read_for_tenant`], naming the component asking for the secret in the audit
+/// event.
+pub fn read_for_tenant_as(requester: &str, tenant: &str, name: &str) -> Result<String> {
+    global()?.read_for_tenant_as(requester, tenant, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
 
-    if !output.status.success() {
-        return Err(anyhow!(
-            "greentic-secrets read {name} failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+    use super::*;
+
+    struct MapProvider(BTreeMap<&'static str, &'static str>);
+
+    impl SecretProvider for MapProvider {
+        fn name(&self) -> &str {
+            "map"
+        }
+
+        fn get(&self, key: &str) -> Result<Option<String>> {
+            Ok(self.0.get(key).map(|value| value.to_string()))
+        }
+    }
+
+    struct FailingTenantProvider;
+
+    impl SecretProvider for FailingTenantProvider {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn get(&self, key: &str) -> Result<Option<String>> {
+            if key.starts_with("tenants/") {
+                bail!("timed out");
+            }
+            Ok(Some("shared".into()))
+        }
+    }
+
+    #[test]
+    fn chain_prefers_tenant_scoped_keys_and_earlier_providers() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("tenants").join("customera")).unwrap();
+        fs::write(root.join("TOKEN"), "shared\n").unwrap();
+        fs::write(root.join("tenants/customera/TOKEN"), "tenant-a\n").unwrap();
+
+        let chain = SecretChain::new()
+            .with_provider(DirProvider::new(root))
+            .with_provider(MapProvider(BTreeMap::from([
+                ("TOKEN", "from-map"),
+                ("ONLY_MAP", "custom"),
+            ])));
+
+        assert_eq!(
+            chain.read_for_tenant("customera", "TOKEN").unwrap(),
+            "tenant-a"
+        );
+        assert_eq!(
+            chain.read_for_tenant("customerb", "TOKEN").unwrap(),
+            "shared"
+        );
+        assert_eq!(chain.read("ONLY_MAP").unwrap(), "custom");
+        let outage = SecretChain::new().with_provider(FailingTenantProvider);
+        assert!(outage.read_for_tenant("customera", "TOKEN").is_err());
+        assert_eq!(outage.read("TOKEN").unwrap(), "shared");
+        assert!(chain.read("../TOKEN").is_err());
+        let missing = chain.read("MISSING").unwrap_err().to_string();
+        assert!(missing.contains("providers tried: dir, map"));
+        assert_eq!(
+            env_key("tenants/customer-a/TOKEN"),
+            "TENANTS_CUSTOMER_A_TOKEN"
+        );
+    }
+
+    #[test]
+    fn cached_values_survive_until_invalidated() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("NATS_JWT"), "old").unwrap();
+        let chain = SecretChain::new()
+            .with_ttl(Duration::from_secs(3600))
+            .with_provider(DirProvider::new(root));
+
+        assert_eq!(chain.read("NATS_JWT").unwrap(), "old");
+        fs::write(root.join("NATS_JWT"), "new").unwrap();
+        assert_eq!(chain.read("NATS_JWT").unwrap(), "old");
+        chain.invalidate("NATS_JWT");
+        assert_eq!(chain.read("NATS_JWT").unwrap(), "new");
     }
 
-    let value = String::from_utf8(output.stdout)
-        .map_err(|err| anyhow!("secret {name} is not utf8: {err}"))?;
-    Ok(Some(value.trim().to_string()))
+    #[cfg(unix)]
+    #[test]
+    fn cli_provider_times_out_parses_json_and_redacts_stderr() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let script = root.join("fake-secrets");
+        fs::write(
+            &script,
+            r#"#!/bin/sh
+case "$2" in
+  SLOW) sleep 5 ;;
+  BROKEN) echo "denied: token=s3cr3t-fragment abcdefghijklmnopqrstuvwxyz012345" >&2; exit 3 ;;
+  *) printf '{"value":"v-%s","version":"7","expires_at":"2999-01-01T00:00:00Z"}' "$2" ;;
+esac
+"#,
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let cli = CliProvider::new(script.to_string_lossy())
+            .with_timeout(Duration::from_millis(200))
+            .with_format(CliFormat::Json);
+        let value = cli.read("NATS_JWT").unwrap();
+        let slow = cli.read("SLOW").unwrap_err();
+        let broken = cli.read("BROKEN").unwrap_err().to_string();
+        let missing = CliProvider::new(root.join("absent").to_string_lossy()).read("X");
+
+        assert_eq!(value.value, "v-NATS_JWT");
+        assert_eq!(value.version.as_deref(), Some("7"));
+        assert!(value.expires_at.is_some());
+        assert!(matches!(slow, CliError::TimedOut { .. }));
+        assert!(broken.contains("token=[redacted]"), "{broken}");
+        assert!(!broken.contains("s3cr3t"));
+        assert!(!broken.contains("abcdefghijklmnop"));
+        assert!(matches!(missing, Err(CliError::NotInstalled { .. })));
+    }
 }
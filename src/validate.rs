@@ -0,0 +1,210 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
+
+use crate::bindings;
+use crate::flow_engine::TenantFlows;
+use crate::loader::{LoadProblem, LoadReport, TenantPack};
+use crate::runner_bridge::pack_paths;
+use crate::secret_policy::SecretPolicy;
+
+/// Outcome of `greentic-demo validate` across every discovered tenant.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub ok: bool,
+    pub tenants: Vec<TenantReport>,
+    /// Problems found while discovering tenants (tenants that never loaded, mismatches).
+    pub problems: Vec<LoadProblem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantReport {
+    pub tenant: String,
+    pub ok: bool,
+    pub pack: PathBuf,
+    pub bindings: PathBuf,
+    pub digest: String,
+    pub checks: Vec<Check>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Failed,
+    /// Not run because an earlier check it depends on failed.
+    Skipped,
+}
+
+impl Check {
+    fn from_result<T>(name: &'static str, result: &Result<T>) -> Self {
+        match result {
+            Ok(_) => Self {
+                name,
+                status: CheckStatus::Ok,
+                message: None,
+            },
+            Err(err) => Self {
+                name,
+                status: CheckStatus::Failed,
+                message: Some(format!("{err:#}")),
+            },
+        }
+    }
+
+    fn skipped(name: &'static str) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Skipped,
+            message: None,
+        }
+    }
+}
+
+impl ValidationReport {
+    /// Runs the bridge's registration steps for each tenant without keeping anything loaded.
+    pub async fn run(load: &LoadReport, secret_policy: &SecretPolicy) -> Self {
+        let mut tenants = Vec::with_capacity(load.tenants.len());
+        for pack in &load.tenants {
+            tenants.push(validate_tenant(pack, secret_policy).await);
+        }
+        Self {
+            ok: load.is_clean() && tenants.iter().all(|tenant| tenant.ok),
+            tenants,
+            problems: load.problems.clone(),
+        }
+    }
+
+    pub fn failed_tenants(&self) -> usize {
+        self.tenants.iter().filter(|tenant| !tenant.ok).count()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for tenant in &self.tenants {
+            let verdict = if tenant.ok { "ok" } else { "FAILED" };
+            writeln!(f, "{} [{verdict}] {}", tenant.tenant, tenant.digest)?;
+            for check in &tenant.checks {
+                let status = match check.status {
+                    CheckStatus::Ok => "ok",
+                    CheckStatus::Failed => "failed",
+                    CheckStatus::Skipped => "skipped",
+                };
+                match &check.message {
+                    Some(message) => writeln!(f, "  {:<16} {status}: {message}", check.name)?,
+                    None => writeln!(f, "  {:<16} {status}", check.name)?,
+                }
+            }
+        }
+        for problem in &self.problems {
+            writeln!(f, "problem: {problem}")?;
+        }
+        write!(
+            f,
+            "{} tenant(s), {} failed, {} load problem(s)",
+            self.tenants.len(),
+            self.failed_tenants(),
+            self.problems.len()
+        )
+    }
+}
+
+async fn validate_tenant(pack: &TenantPack, secret_policy: &SecretPolicy) -> TenantReport {
+    let mut checks = Vec::new();
+
+    let config = bindings::host_config(&pack.bindings_path, &pack.tenant, secret_policy)
+        .with_context(|| format!("failed to load {}", pack.bindings_path.display()));
+    checks.push(Check::from_result("bindings", &config));
+
+    match config {
+        Ok(config) => {
+            let secrets = secret_policy.check(&config);
+            checks.push(Check::from_result("allowed_secrets", &secrets));
+
+            let flows = TenantFlows::load(&pack.tenant, &pack_paths(pack));
+            checks.push(Check::from_result("flows", &flows));
+            match flows {
+                Ok(flows) => {
+                    let messaging = flows
+                        .flow_by_type("messaging")
+                        .map(|_| ())
+                        .ok_or_else(|| anyhow!("no flow of type messaging"));
+                    checks.push(Check::from_result("messaging_flow", &messaging));
+                }
+                Err(_) => checks.push(Check::skipped("messaging_flow")),
+            }
+        }
+        Err(_) => {
+            checks.push(Check::skipped("allowed_secrets"));
+            checks.push(Check::skipped("flows"));
+            checks.push(Check::skipped("messaging_flow"));
+        }
+    }
+
+    TenantReport {
+        tenant: pack.tenant.clone(),
+        ok: checks.iter().all(|check| check.status == CheckStatus::Ok),
+        pack: pack.index_path.clone(),
+        bindings: pack.bindings_source.clone(),
+        digest: pack.digest.clone(),
+        checks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn broken_bindings_skip_dependent_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root).unwrap();
+        let bindings = root.join("bindings.yaml");
+        std::fs::write(&bindings, "tenant: [not, a, string]\n").unwrap();
+        let pack = TenantPack {
+            tenant: "customera".into(),
+            index_path: root.join("index.ygtc"),
+            bindings_path: bindings.clone(),
+            bindings_source: bindings,
+            overlays: Vec::new(),
+            manifest: None,
+            digest: "sha256:00".into(),
+            overlay_digests: Vec::new(),
+        };
+
+        let load = LoadReport {
+            tenants: vec![pack],
+            problems: Vec::new(),
+        };
+        let report = ValidationReport::run(&load, &SecretPolicy::default()).await;
+        assert!(!report.ok);
+        let statuses: Vec<_> = report.tenants[0]
+            .checks
+            .iter()
+            .map(|check| (check.name, check.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("bindings", CheckStatus::Failed),
+                ("allowed_secrets", CheckStatus::Skipped),
+                ("flows", CheckStatus::Skipped),
+                ("messaging_flow", CheckStatus::Skipped),
+            ]
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["tenants"][0]["checks"][0]["status"], "failed");
+    }
+}
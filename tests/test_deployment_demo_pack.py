@@ -18,7 +18,7 @@ class DeploymentDemoPackTests(unittest.TestCase):
         self.assertIn("deploy_generic_iac", text)
 
     def test_index_references_deployment_pack(self) -> None:
-        index_path = ROOT / "examples" / "index.json"
+        index_path = ROOT / "examples" / "deployment" / "index.json"
         index = json.loads(index_path.read_text())
         self.assertIn("deployment-demo", index)
         locator = index["deployment-demo"]["main_pack"]["locator"]